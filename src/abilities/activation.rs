@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::timestep::AddToStep;
+
+use super::{
+    cooldown::AbilityCooldown,
+    heat::{Heat, Overheated},
+    stats::{Stat, StatModifiers},
+    AbilityRejected, Loadout, RejectionReason, UseAbilityEvent,
+};
+
+pub struct ActivationPlugin;
+
+impl Plugin for ActivationPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ActivationMode>()
+            .register_type::<Charging>()
+            .add_step_event::<AbilityTriggerEvent>()
+            .add_step_system(handle_triggers.in_set(ActivationSet));
+    }
+}
+
+/// Turns [`AbilityTriggerEvent`]s into [`UseAbilityEvent`]s.
+#[derive(SystemSet, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ActivationSet;
+
+/// How holding down the trigger of an ability uses it.
+#[derive(Component, Clone, Debug, Default, Deserialize, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub enum ActivationMode {
+    /// Used once when the trigger is pressed.
+    #[default]
+    Tap,
+    /// Used when the trigger is released, with a strength growing from 1 to `max_strength` over
+    /// `max_time` seconds of holding it.
+    Charge { max_time: f32, max_strength: f32 },
+    /// Used whenever it's ready while the trigger is held, adding heat every second on top of the
+    /// heat for each use.
+    Channel { heat_per_second: f32 },
+}
+
+/// Sent every frame the trigger of an ability is pressed, held or released.
+#[derive(Clone, Debug)]
+pub struct AbilityTriggerEvent {
+    pub loadout: Entity,
+    pub ability: usize,
+    pub state: TriggerState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriggerState {
+    Pressed,
+    Held,
+    Released,
+}
+
+/// Inserted on a loadout while it is charging one of its abilities.
+#[derive(Component, Clone, Debug, Reflect, FromReflect)]
+#[reflect(Component, Debug)]
+pub struct Charging {
+    pub ability: Entity,
+    pub elapsed: f32,
+    pub max_time: f32,
+    pub max_strength: f32,
+}
+
+impl Default for Charging {
+    fn default() -> Self {
+        Self {
+            ability: Entity::PLACEHOLDER,
+            elapsed: 0.0,
+            max_time: 1.0,
+            max_strength: 1.0,
+        }
+    }
+}
+
+impl Charging {
+    pub fn percent(&self) -> f32 {
+        if self.max_time <= 0.0 {
+            return 1.0;
+        }
+        (self.elapsed / self.max_time).min(1.0)
+    }
+
+    pub fn strength(&self) -> f32 {
+        1.0 + (self.max_strength - 1.0) * self.percent()
+    }
+}
+
+fn handle_triggers(
+    mut commands: Commands,
+    mut trigger_events: EventReader<AbilityTriggerEvent>,
+    mut ability_events: EventWriter<UseAbilityEvent>,
+    mut rejected_events: EventWriter<AbilityRejected>,
+    mut loadouts: Query<(&Loadout, Option<&mut Charging>, Option<&mut Heat>)>,
+    abilities: Query<(&ActivationMode, &StatModifiers, Option<&AbilityCooldown>)>,
+    overheated: Query<(), With<Overheated>>,
+    time: Res<Time>,
+) {
+    for trigger in trigger_events.iter() {
+        let use_ability = UseAbilityEvent {
+            loadout: trigger.loadout,
+            ability: trigger.ability,
+            strength: 1.0,
+        };
+        let Ok((loadout, charging, heat)) = loadouts.get_mut(trigger.loadout) else { continue; };
+        let Some(&ability_id) = loadout.abilities.get(trigger.ability) else {
+            // Let validation reject it
+            if trigger.state == TriggerState::Pressed {
+                ability_events.send(use_ability);
+            }
+            continue;
+        };
+        let Ok((mode, modifiers, cooldown)) = abilities.get(ability_id) else {
+            // Its definition hasn't been applied yet
+            if trigger.state == TriggerState::Pressed {
+                rejected_events.send(AbilityRejected {
+                    loadout: trigger.loadout,
+                    ability: trigger.ability,
+                    reason: RejectionReason::NotLoaded,
+                });
+            }
+            continue;
+        };
+
+        match (mode, trigger.state) {
+            (ActivationMode::Tap, TriggerState::Pressed) => ability_events.send(use_ability),
+            (
+                &ActivationMode::Charge {
+                    max_time,
+                    max_strength,
+                },
+                TriggerState::Pressed,
+            ) => {
+                commands.entity(trigger.loadout).insert(Charging {
+                    ability: ability_id,
+                    elapsed: 0.0,
+                    max_time,
+                    max_strength,
+                });
+            }
+            (ActivationMode::Charge { .. }, TriggerState::Held) => {
+                let Some(mut charging) = charging else { continue; };
+                if charging.ability == ability_id {
+                    charging.elapsed += time.delta_seconds();
+                }
+            }
+            (ActivationMode::Charge { .. }, TriggerState::Released) => {
+                let Some(charging) = charging else { continue; };
+                if charging.ability == ability_id {
+                    ability_events.send(UseAbilityEvent {
+                        strength: charging.strength(),
+                        ..use_ability
+                    });
+                }
+                commands.entity(trigger.loadout).remove::<Charging>();
+            }
+            // The selected ability changed while charging
+            (_, TriggerState::Released) if charging.is_some() => {
+                commands.entity(trigger.loadout).remove::<Charging>();
+            }
+            (ActivationMode::Channel { .. }, TriggerState::Pressed) => {
+                ability_events.send(use_ability)
+            }
+            (&ActivationMode::Channel { heat_per_second }, TriggerState::Held) => {
+                // Only retry once it can succeed, so holding the trigger doesn't spam rejections
+                if overheated.get(trigger.loadout).is_ok() {
+                    continue;
+                }
+                if let Some(mut heat) = heat {
+                    // Additive heat modifiers are per use, which is already paid by each use
+                    **heat +=
+                        heat_per_second * modifiers.multiplier(Stat::Heat) * time.delta_seconds();
+                }
+                if cooldown.is_none() {
+                    ability_events.send(use_ability);
+                }
+            }
+            _ => {}
+        }
+    }
+}
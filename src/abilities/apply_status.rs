@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    status::{ApplyStatusEvent, StatusEffect},
+    timestep::AddToStep,
+};
+
+use super::{registry::RegisterAbility, teleport::TeleportSet, AbilityActivated, AbilitySet};
+
+pub struct ApplyStatusSideEffectPlugin;
+
+impl Plugin for ApplyStatusSideEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ApplyStatus>()
+            .register_side_effect::<ApplyStatus>("apply_status")
+            .add_step_system(apply_status.in_set(AbilitySet).after(TeleportSet));
+    }
+}
+
+/// Applies status effects to whoever used the ability.
+#[derive(Component, Clone, Debug, Default, Deserialize, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+#[serde(default)]
+pub struct ApplyStatus {
+    pub effects: Vec<StatusEffect>,
+}
+
+fn apply_status(
+    mut activations: EventReader<AbilityActivated>,
+    abilities: Query<&Children>,
+    side_effects: Query<&ApplyStatus>,
+    mut status_events: EventWriter<ApplyStatusEvent>,
+) {
+    for activation in activations.iter() {
+        let Ok(ability_side_effects) = abilities.get(activation.ability) else { continue; };
+
+        for side_effect in side_effects.iter_many(ability_side_effects) {
+            for effect in &side_effect.effects {
+                status_events.send(ApplyStatusEvent {
+                    target: activation.loadout,
+                    effect: effect.clone(),
+                });
+            }
+        }
+    }
+}
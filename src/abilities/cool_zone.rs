@@ -1,14 +1,20 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
-use bevy_rapier2d::prelude::Damping;
+use serde::Deserialize;
 
-use crate::{lifetime::Lifetime, player::Player};
+use crate::{
+    lifetime::Lifetime,
+    player::Player,
+    status::{ApplyStatusEvent, StatusEffect, StatusEffects, StatusKind},
+    timestep::AddToStep,
+};
 
 use super::{
-    cooldown::AbilityCooldown,
-    heat::{Heat, Overheated},
-    AbilitySet, Loadout, SideEffect, UseAbilityEvent,
+    heat::{Heat, HeatSet},
+    registry::RegisterAbility,
+    teleport::TeleportSet,
+    AbilityActivated, AbilitySet,
 };
 
 pub struct CoolZonePlugin;
@@ -16,16 +22,24 @@ pub struct CoolZonePlugin;
 impl Plugin for CoolZonePlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<CoolZone>()
-            .register_type::<Slowed>()
+            .register_type::<SpawnCoolZone>()
+            .register_side_effect::<SpawnCoolZone>("cool_zone")
             .add_startup_system(init_mesh)
-            .add_systems(
-                (spawn_zone, cool_down, slow_movement, unslow_movement)
+            // The zone acts from the step it's spawned in, whether or not another flush in
+            // AbilitySet happens to run in between
+            .add_step_systems(
+                (spawn_zone, apply_system_buffers, cool_down, slow_movement)
                     .chain()
-                    .in_set(AbilitySet),
+                    .in_set(AbilitySet)
+                    .after(HeatSet)
+                    .before(TeleportSet),
             );
     }
 }
 
+/// Seconds objects stay slowed after leaving a zone.
+const SLOW_LINGER: f32 = 0.1;
+
 #[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
 #[reflect(Component, Debug)]
 pub struct CoolZone {
@@ -34,6 +48,28 @@ pub struct CoolZone {
     pub radius: f32,
 }
 
+#[derive(Component, Clone, Debug, Deserialize, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+#[serde(default)]
+pub struct SpawnCoolZone {
+    pub cooling_speed: f32,
+    pub slowdown: f32,
+    pub radius: f32,
+    /// Seconds the zone stays up for
+    pub lifetime: f32,
+}
+
+impl Default for SpawnCoolZone {
+    fn default() -> Self {
+        Self {
+            cooling_speed: 20.0,
+            slowdown: 15.0,
+            radius: 64.0,
+            lifetime: 1.0,
+        }
+    }
+}
+
 #[derive(Resource, Clone, Default, Debug, Deref, DerefMut, Reflect, FromReflect)]
 #[reflect(Resource, Default, Debug)]
 struct CircleMesh(pub Handle<Mesh>);
@@ -47,7 +83,7 @@ fn init_mesh(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    commands.insert_resource(CircleMesh(meshes.add(shape::Circle::new(64.0).into())));
+    commands.insert_resource(CircleMesh(meshes.add(shape::Circle::new(1.0).into())));
     commands.insert_resource(CircleMaterial(materials.add(ColorMaterial {
         color: Color::CYAN.with_a(0.7),
         ..Default::default()
@@ -57,52 +93,44 @@ fn init_mesh(
 fn spawn_zone(
     mut commands: Commands,
     player_transform: Query<&Transform, With<Player>>,
-    loadouts: Query<&Loadout, Without<Overheated>>,
-    mut ability_events: EventReader<UseAbilityEvent>,
-    side_effects: Query<&SideEffect, Without<AbilityCooldown>>,
+    mut activations: EventReader<AbilityActivated>,
+    abilities: Query<&Children>,
+    side_effects: Query<&SpawnCoolZone>,
     mesh: Res<CircleMesh>,
     material: Res<CircleMaterial>,
 ) {
-    for ability in ability_events.iter() {
-        let Ok(loadout) = loadouts.get(ability.loadout) else {
-            continue;
-        };
-
-        let ability_entity = loadout.abilities[ability.ability];
-        let Ok(side_effect) = side_effects.get(ability_entity) else { continue; };
-        if *side_effect != SideEffect::CoolZone {
-            continue;
+    for activation in activations.iter() {
+        let Ok(ability_side_effects) = abilities.get(activation.ability) else { continue; };
+
+        for side_effect in side_effects.iter_many(ability_side_effects) {
+            let Ok(player_transform) = player_transform.get_single() else { return; };
+            let player_position = player_transform.translation.truncate();
+            commands.spawn((
+                CoolZone {
+                    cooling_speed: side_effect.cooling_speed,
+                    slowdown: side_effect.slowdown,
+                    radius: side_effect.radius,
+                },
+                ColorMesh2dBundle {
+                    mesh: mesh.0.clone().into(),
+                    material: material.0.clone(),
+                    transform: Transform::from_translation(player_position.extend(0.5))
+                        .with_scale(Vec3::splat(side_effect.radius)),
+                    ..Default::default()
+                },
+                Lifetime::new(Duration::from_secs_f32(side_effect.lifetime)),
+            ));
         }
-
-        let player_position = player_transform.single().translation.truncate();
-        commands.spawn((
-            CoolZone {
-                cooling_speed: 20.0,
-                slowdown: 15.0,
-                radius: 64.0,
-            },
-            ColorMesh2dBundle {
-                mesh: mesh.0.clone().into(),
-                material: material.0.clone(),
-                transform: Transform::from_translation(player_position.extend(0.5)),
-                ..Default::default()
-            },
-            Lifetime::new(Duration::from_secs(1)),
-        ));
     }
 }
 
-#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
-#[reflect(Component, Debug)]
-pub struct Slowed(pub f32);
-
 fn slow_movement(
-    mut commands: Commands,
-    mut objects: Query<(Entity, &mut Damping, &Transform), Without<Slowed>>,
+    objects: Query<(Entity, &Transform), With<StatusEffects>>,
     zones: Query<(&CoolZone, &Transform)>,
+    mut status_events: EventWriter<ApplyStatusEvent>,
 ) {
     for (zone, zone_transform) in &zones {
-        for (object_id, mut object_damping, object_transform) in &mut objects {
+        for (object_id, object_transform) in &objects {
             let zone_position = zone_transform.translation.truncate();
             let object_position = object_transform.translation.truncate();
 
@@ -110,28 +138,16 @@ fn slow_movement(
                 continue;
             }
 
-            object_damping.linear_damping += zone.slowdown;
-            commands.entity(object_id).insert(Slowed(zone.slowdown));
-        }
-    }
-}
-
-fn unslow_movement(
-    mut commands: Commands,
-    mut objects: Query<(Entity, &Slowed, &mut Damping, &Transform)>,
-    zones: Query<(&CoolZone, &Transform)>,
-) {
-    'next_object: for (object_id, slowdown, mut object_damping, object_transform) in &mut objects {
-        for (zone, zone_transform) in &zones {
-            let zone_position = zone_transform.translation.truncate();
-            let object_position = object_transform.translation.truncate();
-
-            if zone_position.distance(object_position) < zone.radius {
-                continue 'next_object;
-            }
+            status_events.send(ApplyStatusEvent {
+                target: object_id,
+                effect: StatusEffect {
+                    kind: StatusKind::Slow,
+                    strength: zone.slowdown,
+                    duration: SLOW_LINGER,
+                    ..Default::default()
+                },
+            });
         }
-        object_damping.linear_damping -= slowdown.0;
-        commands.entity(object_id).remove::<Slowed>();
     }
 }
 
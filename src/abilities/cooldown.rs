@@ -2,18 +2,25 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use super::{AbilitySet, Loadout, UseAbilityEvent};
+use crate::{enemy::EnemySet, timestep::AddToStep};
+
+use super::{
+    activation::ActivationSet,
+    stats::{Stat, StatModifiers},
+    AbilityActivated, AbilitySet,
+};
 
 pub struct CooldownPlugin;
 
 impl Plugin for CooldownPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<AbilityCooldown>()
-            .add_system(cooldown_timer.before(AbilitySet))
-            .add_systems(
+            .add_step_system(cooldown_timer.before(ActivationSet))
+            .add_step_systems(
                 (apply_cooldown_times, apply_system_buffers)
                     .chain()
-                    .after(AbilitySet),
+                    .after(AbilitySet)
+                    .before(EnemySet::AI),
             );
     }
 }
@@ -54,20 +61,14 @@ fn cooldown_timer(
 
 fn apply_cooldown_times(
     mut commands: Commands,
-    loadouts: Query<&Loadout>,
-    mut ability_events: EventReader<UseAbilityEvent>,
-    ability_cooldown_times: Query<&AbilityCooldownTime, Without<AbilityCooldown>>,
+    mut activations: EventReader<AbilityActivated>,
+    abilities: Query<(&AbilityCooldownTime, &StatModifiers)>,
 ) {
-    for ability in ability_events.iter() {
-        let Ok(loadout) = loadouts.get(ability.loadout) else {
-            warn!("Invalid loadout in ability event");
-            continue;
-        };
-
-        let ability_entity = loadout.abilities[ability.ability];
-        let Ok(default_cooldown) = ability_cooldown_times.get(ability_entity) else { continue; };
+    for activation in activations.iter() {
+        let Ok((default_cooldown, modifiers)) = abilities.get(activation.ability) else { continue; };
+        let cooldown = modifiers.apply(Stat::Cooldown, **default_cooldown).max(0.0);
         commands
-            .entity(ability_entity)
-            .insert(AbilityCooldown::new(**default_cooldown));
+            .entity(activation.ability)
+            .insert(AbilityCooldown::new(cooldown));
     }
 }
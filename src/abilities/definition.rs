@@ -0,0 +1,161 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashSet},
+};
+use ron::extensions::Extensions;
+use serde::Deserialize;
+
+use crate::timestep::AddToStep;
+
+use super::{
+    activation::ActivationMode, cooldown::AbilityCooldownTime, heat::AddHeatOnUse,
+    registry::AbilityRegistry, stats::StatModifiers, AbilitySet,
+};
+
+pub struct AbilityDefinitionPlugin;
+
+impl Plugin for AbilityDefinitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<AbilityDefinition>()
+            .init_asset_loader::<AbilityDefinitionLoader>()
+            .init_resource::<ChangedDefinitions>()
+            .add_system(collect_changed_definitions.in_base_set(CoreSet::PreUpdate))
+            .add_step_system(apply_definitions.before(AbilitySet));
+    }
+}
+
+/// Describes an ability, loaded from `.ability.ron` files.
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "04de1972-0fed-48cf-92c6-06b4268da1e9"]
+pub struct AbilityDefinition {
+    pub name: String,
+    /// Path of the image shown in the ability's UI slot.
+    pub icon: String,
+    pub power: ComponentDefinition,
+    /// Every side effect fires each time the ability is used.
+    #[serde(default)]
+    pub side_effects: Vec<ComponentDefinition>,
+    pub cooldown: f32,
+    #[serde(default)]
+    pub heat: f32,
+    #[serde(default)]
+    pub activation: ActivationMode,
+}
+
+/// A power or side effect, looked up by id in the [`AbilityRegistry`].
+#[derive(Clone, Debug, Deserialize)]
+pub struct ComponentDefinition {
+    pub id: String,
+    #[serde(default)]
+    pub params: Option<ron::Value>,
+}
+
+/// Takes the side effects of an ability from this definition instead of its own, for drafted
+/// abilities. The ability is recomposed whenever either definition is hot reloaded.
+#[derive(Component, Clone, Debug)]
+pub struct SideEffectsFrom(pub Handle<AbilityDefinition>);
+
+#[derive(Default)]
+struct AbilityDefinitionLoader;
+
+impl AssetLoader for AbilityDefinitionLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let definition = ron::Options::default()
+                .with_default_extension(
+                    Extensions::IMPLICIT_SOME | Extensions::UNWRAP_VARIANT_NEWTYPES,
+                )
+                .from_bytes::<AbilityDefinition>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(definition));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ability.ron"]
+    }
+}
+
+/// Definitions loaded or hot reloaded since the last step. Asset events only last two frames,
+/// which can pass without a step.
+#[derive(Resource, Clone, Default, Debug)]
+struct ChangedDefinitions(HashSet<Handle<AbilityDefinition>>);
+
+fn collect_changed_definitions(
+    mut definition_events: EventReader<AssetEvent<AbilityDefinition>>,
+    mut changed: ResMut<ChangedDefinitions>,
+) {
+    for event in definition_events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            changed.0.insert(handle.clone());
+        }
+    }
+}
+
+/// Inserts the components described by the definition onto new ability entities, and onto
+/// existing ones whenever the definition is hot reloaded. Side effects are spawned as children of
+/// the ability, so the same one can be stacked with different params, and come from
+/// [`SideEffectsFrom`] when the ability has it.
+fn apply_definitions(
+    mut commands: Commands,
+    mut changed: ResMut<ChangedDefinitions>,
+    definitions: Res<Assets<AbilityDefinition>>,
+    registry: Res<AbilityRegistry>,
+    abilities: Query<(
+        Entity,
+        &Handle<AbilityDefinition>,
+        Option<&SideEffectsFrom>,
+        Option<&StatModifiers>,
+    )>,
+    added_abilities: Query<(), Added<Handle<AbilityDefinition>>>,
+) {
+    let changed = std::mem::take(&mut changed.0);
+
+    for (ability_id, handle, side_effects_from, modifiers) in &abilities {
+        let added = added_abilities.get(ability_id).is_ok();
+        let side_effects_changed = side_effects_from.is_some_and(|from| changed.contains(&from.0));
+        if !added && !changed.contains(handle) && !side_effects_changed {
+            continue;
+        }
+        let Some(definition) = definitions.get(handle) else { continue; };
+        let side_effects = match side_effects_from {
+            Some(from) => {
+                let Some(from) = definitions.get(&from.0) else { continue; };
+                &from.side_effects
+            }
+            None => &definition.side_effects,
+        };
+
+        let mut ability = commands.entity(ability_id);
+        // Not only when added, the definition can finish loading after the ability was spawned
+        if modifiers.is_none() {
+            ability.insert(StatModifiers::default());
+        }
+        registry.remove_powers(&mut ability);
+        ability.despawn_descendants();
+
+        if let Err(error) = registry.insert_power(&mut ability, &definition.power) {
+            warn!("Invalid power in ability definition: {error}");
+        }
+        ability.with_children(|parent| {
+            for side_effect in side_effects {
+                if let Err(error) =
+                    registry.insert_side_effect(&mut parent.spawn_empty(), side_effect)
+                {
+                    warn!("Invalid side effect in ability definition: {error}");
+                }
+            }
+        });
+        ability.insert((
+            AbilityCooldownTime(definition.cooldown),
+            AddHeatOnUse(definition.heat),
+            definition.activation.clone(),
+        ));
+    }
+}
@@ -1,88 +1,368 @@
-use std::time::Duration;
+use std::{f32::consts::PI, time::Duration};
 
 use bevy::prelude::*;
+use bevy_rapier2d::prelude::{
+    ActiveEvents, Collider, QueryFilter, RapierContext, RigidBody, Sensor, Velocity,
+};
+use serde::{de::Error, Deserialize, Deserializer};
 
 use crate::{
+    arena::Arena,
     explosion::{ExplosionEvent, HandleExplosionSet},
+    lifetime::Lifetime,
     mouse_position::MousePosition,
+    player::Player,
+    projectile::{Projectile, ProjectileExplosion},
+    status::StatusEffect,
+    timestep::AddToStep,
 };
 
 use super::{
-    cooldown::AbilityCooldown, heat::Overheated, AbilitySet, Loadout, Power, UseAbilityEvent,
+    registry::RegisterAbility,
+    stats::{Stat, StatModifiers},
+    teleport::TeleportSet,
+    wall::IceWall,
+    AbilityActivated, AbilitySet,
 };
 
 pub struct FireballPlugin;
 
 impl Plugin for FireballPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Fireball>().add_systems(
-            (spawn_fireball, handle_fireball_explosion)
-                .in_set(AbilitySet)
-                .before(HandleExplosionSet),
-        );
+        app.register_type::<Fireball>()
+            .register_type::<Lobbed>()
+            .register_type::<LandingMarker>()
+            .register_type::<Delivery>()
+            .register_type::<FireballPower>()
+            .register_power::<FireballPower>("fireball", &[Stat::Damage, Stat::Range])
+            .add_step_systems(
+                (
+                    spawn_fireball,
+                    // Fireballs move from the step they're spawned in, whatever other flushes in
+                    // AbilitySet do
+                    apply_system_buffers,
+                    move_lobbed_fireballs,
+                    handle_fireball_explosion,
+                )
+                    .chain()
+                    .in_set(AbilitySet)
+                    .before(TeleportSet)
+                    .before(HandleExplosionSet),
+            );
     }
 }
 
 #[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
 #[reflect(Component, Debug)]
 pub struct Fireball {
+    /// Explodes when this finishes
     pub timer: Timer,
+    pub range: f32,
+    pub force: f32,
+    pub damage: f32,
+    pub effects: Vec<StatusEffect>,
+    pub owner: Option<Entity>,
+}
+
+/// Moves a [`Fireball`] in an arc from `start` to `target` over the length of its timer.
+#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component, Debug)]
+pub struct Lobbed {
+    pub start: Vec2,
+    pub target: Vec2,
+}
+
+#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component, Debug)]
+pub struct LandingMarker;
+
+// Params are read through `ron::Value`, which drops enum variant names, so this is written as a
+// string in ability definitions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Reflect, FromReflect)]
+#[serde(try_from = "String")]
+pub enum Delivery {
+    /// Appears at the cursor and explodes after `delay`
+    #[default]
+    Instant,
+    /// Flies over everything to the cursor
+    Lobbed,
+    /// Flies towards the cursor and explodes on the first thing it hits, or once it gets there
+    Projectile,
+}
+
+impl TryFrom<String> for Delivery {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "instant" => Ok(Self::Instant),
+            "lobbed" => Ok(Self::Lobbed),
+            "projectile" => Ok(Self::Projectile),
+            _ => Err(format!("unknown fireball delivery \"{value}\"")),
+        }
+    }
+}
+
+#[derive(Component, Clone, Debug, Deserialize, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+#[serde(default)]
+pub struct FireballPower {
+    pub delivery: Delivery,
+    /// Seconds between casting the fireball and it exploding, for instant delivery
+    #[serde(deserialize_with = "non_negative")]
+    pub delay: f32,
+    #[serde(deserialize_with = "positive")]
+    pub max_cast_range: f32,
+    /// Pixels per second, for lobbed and projectile delivery
+    #[serde(deserialize_with = "positive")]
+    pub speed: f32,
+    pub range: f32,
+    pub force: f32,
+    pub damage: f32,
+    /// Applied to everything caught in the explosion
+    pub effects: Vec<StatusEffect>,
+}
+
+impl Default for FireballPower {
+    fn default() -> Self {
+        Self {
+            delivery: Delivery::Instant,
+            delay: 0.25,
+            max_cast_range: 160.0,
+            speed: 256.0,
+            range: 32.0,
+            force: 100.0,
+            damage: 200.0,
+            effects: Vec::new(),
+        }
+    }
+}
+
+/// Rejects values the flight and fuse times can't be computed from, like a zero speed.
+fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let value = f32::deserialize(deserializer)?;
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        Err(D::Error::custom(format!(
+            "expected a positive number, got {value}"
+        )))
+    }
+}
+
+fn non_negative<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let value = f32::deserialize(deserializer)?;
+    if value >= 0.0 {
+        Ok(value)
+    } else {
+        Err(D::Error::custom(format!(
+            "expected zero or more, got {value}"
+        )))
+    }
 }
 
+/// Distance kept from the walls when they block the cast.
+const WALL_MARGIN: f32 = 4.0;
+/// Highest point of a lobbed fireball's arc, relative to the distance it travels.
+const LOB_HEIGHT: f32 = 0.25;
+
 fn spawn_fireball(
     mut commands: Commands,
-    loadouts: Query<&Loadout, Without<Overheated>>,
-    mut ability_events: EventReader<UseAbilityEvent>,
+    mut activations: EventReader<AbilityActivated>,
     mouse_position: Res<MousePosition>,
-    powers: Query<&Power, Without<AbilityCooldown>>,
+    players: Query<(&Transform, &Velocity), With<Player>>,
+    powers: Query<(&FireballPower, &StatModifiers)>,
+    walls: Query<(), Or<(With<IceWall>, With<Arena>)>>,
+    rapier_context: Res<RapierContext>,
 ) {
-    for ability in ability_events.iter() {
-        let Ok(loadout) = loadouts.get(ability.loadout) else {
+    let is_wall = |entity| walls.contains(entity);
+    let filter = QueryFilter::only_fixed().predicate(&is_wall);
+
+    for activation in activations.iter() {
+        let Ok((power, modifiers)) = powers.get(activation.ability) else { continue; };
+        let Ok((player_transform, player_velocity)) = players.get(activation.loadout) else {
             continue;
         };
+        let player_position = player_transform.translation.truncate();
 
-        let ability_entity = loadout.abilities[ability.ability];
-        let Ok(power) = powers.get(ability_entity) else { continue; };
-        if *power != Power::Fireball {
-            continue;
+        // Cast towards the cursor, stopping at the max range or the first wall in the way, like
+        // projectiles bounce off
+        let max_cast_range = modifiers.apply(Stat::Range, power.max_cast_range);
+        let delta = (**mouse_position - player_position).clamp_length_max(max_cast_range);
+        let direction = delta.normalize_or_zero();
+        let wall_hit =
+            rapier_context.cast_ray(player_position, direction, delta.length(), true, filter);
+        let distance = match wall_hit {
+            Some((_, wall_distance)) => (wall_distance - WALL_MARGIN).max(0.0),
+            None => delta.length(),
+        };
+        let target = player_position + direction * distance;
+
+        let fireball = Fireball {
+            timer: Timer::from_seconds(power.delay, TimerMode::Once),
+            range: power.range * activation.strength,
+            force: power.force * activation.strength,
+            damage: modifiers.apply(Stat::Damage, power.damage),
+            effects: power.effects.clone(),
+            owner: Some(activation.loadout),
+        };
+        let sprite = Sprite {
+            color: Color::RED.with_a(0.0),
+            custom_size: Some(Vec2::splat(16.0)),
+            ..Default::default()
+        };
+
+        match power.delivery {
+            Delivery::Instant => {
+                commands.spawn((
+                    fireball,
+                    SpriteBundle {
+                        sprite,
+                        transform: Transform::from_translation(target.extend(1.0)),
+                        ..Default::default()
+                    },
+                ));
+            }
+            Delivery::Lobbed => {
+                let flight_time = Duration::from_secs_f32(distance / power.speed);
+                commands.spawn((
+                    Fireball {
+                        timer: Timer::new(flight_time, TimerMode::Once),
+                        ..fireball.clone()
+                    },
+                    Lobbed {
+                        start: player_position,
+                        target,
+                    },
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::RED,
+                            ..sprite.clone()
+                        },
+                        transform: Transform::from_translation(player_position.extend(1.0)),
+                        ..Default::default()
+                    },
+                ));
+                commands.spawn((
+                    LandingMarker,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::RED.with_a(0.3),
+                            custom_size: Some(Vec2::splat(fireball.range * 2.0)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_translation(target.extend(0.5)),
+                        ..Default::default()
+                    },
+                    Lifetime::new(flight_time),
+                ));
+            }
+            Delivery::Projectile => {
+                // With the cursor on the player, shoot the way it's moving as far as it goes
+                let (direction, distance) = match direction {
+                    Vec2::ZERO => (player_velocity.linvel.normalize_or_zero(), max_cast_range),
+                    direction => (direction, distance),
+                };
+                if direction == Vec2::ZERO {
+                    continue;
+                }
+                // Spawned in front of the player, so it doesn't hit it
+                let start = player_position + direction * 16.0;
+                let flight_time = Duration::from_secs_f32((distance - 16.0).max(0.0) / power.speed);
+                commands.spawn((
+                    Fireball {
+                        timer: Timer::new(flight_time, TimerMode::Once),
+                        ..fireball.clone()
+                    },
+                    Projectile {
+                        owner: Some(activation.loadout),
+                        explosion: Some(ProjectileExplosion {
+                            range: fireball.range,
+                            force: fireball.force,
+                            damage: fireball.damage,
+                            effects: fireball.effects.clone(),
+                        }),
+                        ..Default::default()
+                    },
+                    RigidBody::Dynamic,
+                    Velocity::linear(direction * power.speed),
+                    Collider::ball(4.0),
+                    Sensor,
+                    ActiveEvents::COLLISION_EVENTS,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::RED,
+                            custom_size: Some(Vec2::splat(8.0)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_translation(start.extend(1.0)),
+                        ..Default::default()
+                    },
+                ));
+            }
         }
+    }
+}
+
+fn move_lobbed_fireballs(mut fireballs: Query<(&mut Transform, &Fireball, &Lobbed)>) {
+    for (mut fireball_transform, fireball, lobbed) in &mut fireballs {
+        let progress = fireball.timer.percent();
+        let height = (progress * PI).sin() * lobbed.start.distance(lobbed.target) * LOB_HEIGHT;
 
-        commands.spawn((
-            Fireball {
-                timer: Timer::new(Duration::from_millis(250), TimerMode::Once),
-            },
-            SpriteBundle {
-                sprite: Sprite {
-                    color: Color::RED.with_a(0.0),
-                    custom_size: Some(Vec2::splat(16.0)),
-                    ..Default::default()
-                },
-                transform: Transform::from_translation(mouse_position.extend(1.0)),
-                ..Default::default()
-            },
-        ));
+        let position = lobbed.start.lerp(lobbed.target, progress) + Vec2::Y * height;
+        fireball_transform.translation = position.extend(fireball_transform.translation.z);
     }
 }
 
 fn handle_fireball_explosion(
     mut commands: Commands,
     time: Res<Time>,
-    mut fireballs: Query<(Entity, &Transform, &mut Sprite, &mut Fireball)>,
+    mut fireballs: Query<(
+        Entity,
+        &Transform,
+        &mut Sprite,
+        &mut Fireball,
+        Option<&Lobbed>,
+        Option<&mut Projectile>,
+    )>,
     mut explosion_events: EventWriter<ExplosionEvent>,
 ) {
-    for (fireball_id, fireball_transform, mut fireball_sprite, mut fireball) in &mut fireballs {
+    for (
+        fireball_id,
+        fireball_transform,
+        mut fireball_sprite,
+        mut fireball,
+        lobbed,
+        mut projectile,
+    ) in &mut fireballs
+    {
         if !fireball.timer.tick(time.delta()).just_finished() {
-            fireball_sprite.color = fireball_sprite
-                .color
-                .with_a(fireball.timer.elapsed_secs() * 0.7);
+            // Instant fireballs fade in until they explode
+            if lobbed.is_none() && projectile.is_none() {
+                fireball_sprite.color = fireball_sprite
+                    .color
+                    .with_a(fireball.timer.elapsed_secs() * 0.7);
+            }
             continue;
         }
+        // Projectile fireballs hitting something on the step they land already exploded
+        if let Some(projectile) = &mut projectile {
+            if projectile.explosion.take().is_none() {
+                continue;
+            }
+        }
 
+        let position = match lobbed {
+            Some(lobbed) => lobbed.target,
+            None => fireball_transform.translation.truncate(),
+        };
         explosion_events.send(ExplosionEvent {
-            position: fireball_transform.translation.truncate(),
-            range: 32.0,
-            force: 100.0,
-            damage: 200.0,
+            position,
+            range: fireball.range,
+            force: fireball.force,
+            damage: fireball.damage,
+            effects: fireball.effects.clone(),
+            source: fireball.owner,
         });
 
         commands.entity(fireball_id).despawn_recursive();
@@ -1,8 +1,11 @@
 use bevy::prelude::*;
 
-use crate::player::Player;
+use crate::{player::Player, timestep::AddToStep};
 
-use super::{AbilitySet, Loadout, UseAbilityEvent};
+use super::{
+    stats::{Stat, StatModifiers},
+    AbilityActivated, AbilitySet,
+};
 
 pub struct HeatPlugin;
 
@@ -10,8 +13,10 @@ impl Plugin for HeatPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Heat>()
             .register_type::<Overheated>()
-            .add_system(add_heat_on_ability.in_set(AbilitySet))
-            .add_systems(
+            .register_type::<HeatConfig>()
+            .init_resource::<HeatConfig>()
+            .add_step_system(add_heat_on_ability.in_set(AbilitySet).in_set(HeatSet))
+            .add_step_systems(
                 (add_overheated, remove_heat, remove_overheated)
                     .chain()
                     .after(AbilitySet),
@@ -19,8 +24,31 @@ impl Plugin for HeatPlugin {
     }
 }
 
-const OVERHEAT_THRESHOLD: f32 = 100.0;
-const HEAT_REMOVAL_RATE: f32 = 20.0;
+/// Adds the heat of abilities used this step.
+#[derive(SystemSet, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct HeatSet;
+
+pub const OVERHEAT_THRESHOLD: f32 = 100.0;
+pub const HEAT_REMOVAL_RATE: f32 = 20.0;
+
+/// How much heat it takes to overheat and how fast it goes away, [`OVERHEAT_THRESHOLD`] and
+/// [`HEAT_REMOVAL_RATE`] unless they're being tuned.
+#[derive(Resource, Clone, Debug, Reflect, FromReflect)]
+#[reflect(Resource, Default, Debug)]
+pub struct HeatConfig {
+    pub overheat_threshold: f32,
+    /// Heat removed every second
+    pub removal_rate: f32,
+}
+
+impl Default for HeatConfig {
+    fn default() -> Self {
+        Self {
+            overheat_threshold: OVERHEAT_THRESHOLD,
+            removal_rate: HEAT_REMOVAL_RATE,
+        }
+    }
+}
 
 #[derive(Component, Clone, Default, Deref, DerefMut, Debug, Reflect, FromReflect)]
 #[reflect(Component, Default, Debug)]
@@ -33,9 +61,10 @@ pub struct Overheated;
 fn add_overheated(
     mut commands: Commands,
     heats: Query<(Entity, &Heat), (Changed<Heat>, Without<Overheated>)>,
+    config: Res<HeatConfig>,
 ) {
     for (entity, heat) in &heats {
-        if **heat > OVERHEAT_THRESHOLD {
+        if **heat > config.overheat_threshold {
             commands.entity(entity).insert(Overheated);
             info!("OVERHEATED");
         }
@@ -59,23 +88,22 @@ fn remove_overheated(
 pub struct AddHeatOnUse(pub f32);
 
 fn add_heat_on_ability(
-    mut ability_events: EventReader<UseAbilityEvent>,
-    mut players: Query<(&mut Heat, &Loadout), (With<Player>, Without<Overheated>)>,
-    ability: Query<&AddHeatOnUse>,
+    mut activations: EventReader<AbilityActivated>,
+    mut players: Query<&mut Heat, With<Player>>,
+    ability: Query<(&AddHeatOnUse, &StatModifiers)>,
 ) {
-    for ability_event in ability_events.iter() {
-        let Ok((mut heat, loadout)) = players.get_mut(ability_event.loadout) else {
+    for activation in activations.iter() {
+        let Ok(mut heat) = players.get_mut(activation.loadout) else {
             continue;
         };
-        let ability_entity = loadout.abilities[ability_event.ability];
-        let Ok(heat_to_add) = ability.get(ability_entity) else { continue; };
+        let Ok((heat_to_add, modifiers)) = ability.get(activation.ability) else { continue; };
 
-        **heat += **heat_to_add;
+        **heat += modifiers.apply(Stat::Heat, **heat_to_add).max(0.0);
     }
 }
 
-fn remove_heat(mut heats: Query<&mut Heat>, time: Res<Time>) {
+fn remove_heat(mut heats: Query<&mut Heat>, config: Res<HeatConfig>, time: Res<Time>) {
     for mut heat in &mut heats {
-        **heat = (**heat - HEAT_REMOVAL_RATE * time.delta_seconds()).max(0.0);
+        **heat = (**heat - config.removal_rate * time.delta_seconds()).max(0.0);
     }
 }
@@ -1,30 +1,46 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
+use serde::Deserialize;
 
-use crate::{enemy::Target, lifetime::Lifetime, player::Player};
+use crate::{enemy::Target, lifetime::Lifetime, player::Player, timestep::AddToStep};
 
-use super::{
-    cooldown::AbilityCooldown, heat::Overheated, teleport::TeleportSet, AbilitySet, Loadout,
-    SideEffect, UseAbilityEvent,
-};
+use super::{registry::RegisterAbility, teleport::TeleportSet, AbilityActivated, AbilitySet};
 
 pub struct InvisibilityWithShadowPlugin;
 
 impl Plugin for InvisibilityWithShadowPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Invisible>().add_systems(
-            (
-                ability,
-                on_shadow_death,
-                apply_system_buffers,
-                on_add_invisible,
-                on_removed_invisible,
-            )
-                .chain()
-                .in_set(AbilitySet)
-                .before(TeleportSet),
-        );
+        app.register_type::<Invisible>()
+            .register_type::<InvisibleWithShadow>()
+            .register_side_effect::<InvisibleWithShadow>("invisible_with_shadow")
+            .add_step_systems(
+                (
+                    ability,
+                    apply_system_buffers,
+                    on_shadows_gone,
+                    apply_system_buffers,
+                    on_add_invisible,
+                    on_visible_again,
+                )
+                    .chain()
+                    .in_set(AbilitySet)
+                    .before(TeleportSet),
+            );
+    }
+}
+
+#[derive(Component, Clone, Debug, Deserialize, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+#[serde(default)]
+pub struct InvisibleWithShadow {
+    /// Seconds the shadow survives for, unless it is killed first
+    pub duration: f32,
+}
+
+impl Default for InvisibleWithShadow {
+    fn default() -> Self {
+        Self { duration: 3.0 }
     }
 }
 
@@ -32,18 +48,22 @@ impl Plugin for InvisibilityWithShadowPlugin {
 #[reflect(Component, Default, Debug)]
 pub struct Invisible;
 
+/// Was a [`Target`] before turning [`Invisible`], and becomes one again once visible.
+#[derive(Component, Clone, Default, Debug)]
+struct Hidden;
+
 fn on_add_invisible(mut commands: Commands, added_invisible: Query<Entity, Added<Invisible>>) {
     for entity in &added_invisible {
-        commands.entity(entity).remove::<Target>();
+        commands.entity(entity).remove::<Target>().insert(Hidden);
     }
 }
 
-fn on_removed_invisible(
+fn on_visible_again(
     mut commands: Commands,
-    mut removed_invisible: RemovedComponents<Invisible>,
+    visible: Query<Entity, (With<Hidden>, Without<Invisible>)>,
 ) {
-    for entity in removed_invisible.iter() {
-        commands.entity(entity).insert(Target);
+    for entity in &visible {
+        commands.entity(entity).remove::<Hidden>().insert(Target);
     }
 }
 
@@ -54,44 +74,46 @@ pub struct Shadow;
 fn ability(
     mut commands: Commands,
     player: Query<(Entity, &Transform, &Handle<Image>, &Sprite), With<Player>>,
-    loadouts: Query<&Loadout, Without<Overheated>>,
-    mut ability_events: EventReader<UseAbilityEvent>,
-    side_effects: Query<&SideEffect, Without<AbilityCooldown>>,
+    mut activations: EventReader<AbilityActivated>,
+    abilities: Query<&Children>,
+    side_effects: Query<&InvisibleWithShadow>,
 ) {
-    for ability in ability_events.iter() {
-        let Ok(loadout) = loadouts.get(ability.loadout) else {
-            continue;
-        };
-
-        let ability_entity = loadout.abilities[ability.ability];
-        let Ok(side_effect) = side_effects.get(ability_entity) else { continue; };
-        if *side_effect != SideEffect::InvisibleWithShadow {
-            continue;
-        }
+    for activation in activations.iter() {
+        let Ok(ability_side_effects) = abilities.get(activation.ability) else { continue; };
+
+        for side_effect in side_effects.iter_many(ability_side_effects) {
+            let Ok((player_id, player_transform, player_texture, player_sprite)) =
+                player.get_single()
+            else {
+                return;
+            };
+            commands.entity(player_id).insert(Invisible);
 
-        let (player_id, player_transform, player_texture, player_sprite) = player.single();
-        commands.entity(player_id).insert(Invisible);
-
-        commands.spawn((
-            Shadow,
-            Target,
-            Lifetime::new(Duration::from_secs(3)),
-            SpriteBundle {
-                sprite: player_sprite.clone(),
-                transform: *player_transform,
-                texture: player_texture.clone(),
-                ..Default::default()
-            },
-        ));
+            commands.spawn((
+                Shadow,
+                Target,
+                Lifetime::new(Duration::from_secs_f32(side_effect.duration)),
+                SpriteBundle {
+                    sprite: player_sprite.clone(),
+                    transform: *player_transform,
+                    texture: player_texture.clone(),
+                    ..Default::default()
+                },
+            ));
+        }
     }
 }
 
-fn on_shadow_death(
+/// Invisibility lasts as long as the shadows, whether they are killed or expire.
+fn on_shadows_gone(
     mut commands: Commands,
-    mut removed_shadows: RemovedComponents<Shadow>,
-    player: Query<Entity, With<Player>>,
+    shadows: Query<(), With<Shadow>>,
+    players: Query<Entity, (With<Player>, With<Invisible>)>,
 ) {
-    for _ in removed_shadows.iter() {
-        commands.entity(player.single()).remove::<Invisible>();
+    if !shadows.is_empty() {
+        return;
+    }
+    for player in &players {
+        commands.entity(player).remove::<Invisible>();
     }
 }
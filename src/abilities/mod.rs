@@ -1,19 +1,38 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
+use bevy_kira_audio::{Audio, AudioControl};
+
+use crate::{assets::GameAssets, status::Incapacitated, timestep::AddToStep};
 
 use self::{
-    cool_zone::CoolZonePlugin, cooldown::CooldownPlugin, fireball::FireballPlugin,
-    heat::HeatPlugin, invisibility::InvisibilityWithShadowPlugin, shotgun::ShotgunPlugin,
-    take_damage::TakeDamageSideEffectPlugin, teleport::TeleportPowerPlugin, wall::WallPowerPlugin,
+    activation::{ActivationPlugin, ActivationSet},
+    apply_status::ApplyStatusSideEffectPlugin,
+    cool_zone::CoolZonePlugin,
+    cooldown::{AbilityCooldown, CooldownPlugin},
+    definition::AbilityDefinitionPlugin,
+    fireball::FireballPlugin,
+    heat::{HeatPlugin, Overheated},
+    invisibility::InvisibilityWithShadowPlugin,
+    registry::AbilityRegistry,
+    shotgun::ShotgunPlugin,
+    stats::StatsPlugin,
+    take_damage::TakeDamageSideEffectPlugin,
+    teleport::TeleportPowerPlugin,
+    wall::WallPowerPlugin,
 };
 
 pub struct AbilitiesPlugin;
 
+pub mod activation;
+pub mod apply_status;
 pub mod cool_zone;
 pub mod cooldown;
+pub mod definition;
 pub mod fireball;
 pub mod heat;
 pub mod invisibility;
+pub mod registry;
 pub mod shotgun;
+pub mod stats;
 pub mod take_damage;
 pub mod teleport;
 pub mod wall;
@@ -23,41 +42,29 @@ pub struct AbilitySet;
 
 impl Plugin for AbilitiesPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Power>()
-            .register_type::<SideEffect>()
-            .register_type::<Loadout>()
-            .add_event::<UseAbilityEvent>()
+        app.register_type::<Loadout>()
+            .init_resource::<AbilityRegistry>()
+            .add_step_event::<UseAbilityEvent>()
+            .add_step_event::<AbilityActivated>()
+            .add_frame_step_event::<AbilityRejected>()
+            .add_step_system(validate_ability_use.after(ActivationSet).before(AbilitySet))
+            .add_step_system(play_rejected_sound.after(validate_ability_use))
+            .add_plugin(AbilityDefinitionPlugin)
+            .add_plugin(ActivationPlugin)
             .add_plugin(CooldownPlugin)
+            .add_plugin(StatsPlugin)
             .add_plugin(TeleportPowerPlugin)
             .add_plugin(FireballPlugin)
             .add_plugin(WallPowerPlugin)
             .add_plugin(ShotgunPlugin)
             .add_plugin(HeatPlugin)
             .add_plugin(TakeDamageSideEffectPlugin)
+            .add_plugin(ApplyStatusSideEffectPlugin)
             .add_plugin(InvisibilityWithShadowPlugin)
             .add_plugin(CoolZonePlugin);
     }
 }
 
-#[derive(Component, Clone, Debug, Default, PartialEq, Eq, Hash, Reflect, FromReflect)]
-#[reflect(Component, Debug)]
-pub enum Power {
-    #[default]
-    Teleport,
-    Fireball,
-    IceWall,
-    Shotgun,
-}
-
-#[derive(Component, Clone, Debug, Default, PartialEq, Eq, Hash, Reflect, FromReflect)]
-#[reflect(Component, Debug)]
-pub enum SideEffect {
-    #[default]
-    TakeDamage,
-    CoolZone,
-    InvisibleWithShadow,
-}
-
 #[derive(Component, Clone, Default, Debug, Reflect, FromReflect)]
 #[reflect(Component, Default, Debug)]
 pub struct Loadout {
@@ -68,4 +75,83 @@ pub struct Loadout {
 pub struct UseAbilityEvent {
     pub loadout: Entity,
     pub ability: usize,
+    /// Multiplier for charged abilities, 1.0 otherwise.
+    pub strength: f32,
+}
+
+/// Sent for every [`UseAbilityEvent`] that passed the cooldown, overheat and status checks.
+#[derive(Clone, Debug)]
+pub struct AbilityActivated {
+    pub loadout: Entity,
+    pub ability: Entity,
+    pub strength: f32,
+}
+
+/// Sent for every [`UseAbilityEvent`] that did not result in an [`AbilityActivated`].
+#[derive(Clone, Debug)]
+pub struct AbilityRejected {
+    pub loadout: Entity,
+    pub ability: usize,
+    pub reason: RejectionReason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectionReason {
+    Cooldown,
+    Overheated,
+    InvalidSlot,
+    /// The loadout is frozen or stunned
+    Incapacitated,
+    /// The ability's definition hasn't loaded yet
+    NotLoaded,
+}
+
+fn validate_ability_use(
+    mut ability_events: EventReader<UseAbilityEvent>,
+    mut activated_events: EventWriter<AbilityActivated>,
+    mut rejected_events: EventWriter<AbilityRejected>,
+    loadouts: Query<(&Loadout, Option<&Overheated>, Option<&Incapacitated>)>,
+    ready_abilities: Query<(), Without<AbilityCooldown>>,
+    mut activated: Local<HashSet<Entity>>,
+) {
+    activated.clear();
+    for ability in ability_events.iter() {
+        let reason = match loadouts.get(ability.loadout) {
+            Ok((_, _, Some(_))) => RejectionReason::Incapacitated,
+            Ok((loadout, None, None)) => match loadout.abilities.get(ability.ability) {
+                // An ability used twice in one frame would otherwise skip its cooldown
+                Some(&ability_entity)
+                    if ready_abilities.get(ability_entity).is_ok()
+                        && activated.insert(ability_entity) =>
+                {
+                    activated_events.send(AbilityActivated {
+                        loadout: ability.loadout,
+                        ability: ability_entity,
+                        strength: ability.strength,
+                    });
+                    continue;
+                }
+                Some(_) => RejectionReason::Cooldown,
+                None => RejectionReason::InvalidSlot,
+            },
+            Ok((_, Some(_), None)) => RejectionReason::Overheated,
+            Err(_) => RejectionReason::InvalidSlot,
+        };
+
+        rejected_events.send(AbilityRejected {
+            loadout: ability.loadout,
+            ability: ability.ability,
+            reason,
+        });
+    }
+}
+
+fn play_rejected_sound(
+    mut rejected_events: EventReader<AbilityRejected>,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+) {
+    if rejected_events.iter().count() > 0 {
+        audio.play(assets.dud_sound.clone());
+    }
 }
@@ -0,0 +1,160 @@
+use std::fmt;
+
+use bevy::{ecs::system::EntityCommands, prelude::*, utils::HashMap};
+use serde::de::DeserializeOwned;
+
+use super::{
+    definition::ComponentDefinition,
+    stats::{PowerStats, Stat},
+};
+
+/// Maps the ids used in ability definitions to the components implementing them. The component
+/// is deserialized from the definition's params and inserted onto the ability entity, and the
+/// plugin that registered it reacts to [`AbilityActivated`](super::AbilityActivated) for it.
+/// Powers are registered with the [`Stat`]s they read, which are inserted as [`PowerStats`].
+#[derive(Resource, Default)]
+pub struct AbilityRegistry {
+    powers: HashMap<String, AbilityComponent>,
+    side_effects: HashMap<String, AbilityComponent>,
+}
+
+#[derive(Clone, Copy)]
+struct AbilityComponent {
+    insert: fn(&mut EntityCommands, Option<ron::Value>) -> Result<(), ron::Error>,
+    remove: fn(&mut EntityCommands),
+    stats: &'static [Stat],
+}
+
+impl AbilityComponent {
+    fn new<T: Component + Default + DeserializeOwned>(stats: &'static [Stat]) -> Self {
+        Self {
+            insert: insert_component::<T>,
+            remove: remove_component::<T>,
+            stats,
+        }
+    }
+}
+
+fn insert_component<T: Component + Default + DeserializeOwned>(
+    entity: &mut EntityCommands,
+    params: Option<ron::Value>,
+) -> Result<(), ron::Error> {
+    let component = match params {
+        Some(params) => params.into_rust::<T>()?,
+        None => T::default(),
+    };
+    entity.insert(component);
+    Ok(())
+}
+
+fn remove_component<T: Component>(entity: &mut EntityCommands) {
+    entity.remove::<T>();
+}
+
+#[derive(Debug)]
+pub enum AbilityRegistryError {
+    UnknownId(String),
+    InvalidParams(String, ron::Error),
+}
+
+impl fmt::Display for AbilityRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownId(id) => write!(f, "no power or side effect registered as \"{id}\""),
+            Self::InvalidParams(id, error) => write!(f, "invalid params for \"{id}\": {error}"),
+        }
+    }
+}
+
+impl AbilityRegistry {
+    pub fn register_power<T: Component + Default + DeserializeOwned>(
+        &mut self,
+        id: &str,
+        stats: &'static [Stat],
+    ) {
+        self.powers
+            .insert(id.to_owned(), AbilityComponent::new::<T>(stats));
+    }
+
+    pub fn register_side_effect<T: Component + Default + DeserializeOwned>(&mut self, id: &str) {
+        self.side_effects
+            .insert(id.to_owned(), AbilityComponent::new::<T>(&[]));
+    }
+
+    pub fn insert_power(
+        &self,
+        entity: &mut EntityCommands,
+        definition: &ComponentDefinition,
+    ) -> Result<(), AbilityRegistryError> {
+        let stats = Self::insert(&self.powers, entity, definition)?;
+        entity.insert(PowerStats(stats));
+        Ok(())
+    }
+
+    pub fn insert_side_effect(
+        &self,
+        entity: &mut EntityCommands,
+        definition: &ComponentDefinition,
+    ) -> Result<(), AbilityRegistryError> {
+        Self::insert(&self.side_effects, entity, definition).map(|_| ())
+    }
+
+    /// Removes every registered power component from the entity.
+    pub fn remove_powers(&self, entity: &mut EntityCommands) {
+        for component in self.powers.values() {
+            (component.remove)(entity);
+        }
+        entity.remove::<PowerStats>();
+    }
+
+    /// Inserts the component and returns the stats it was registered with.
+    fn insert(
+        components: &HashMap<String, AbilityComponent>,
+        entity: &mut EntityCommands,
+        definition: &ComponentDefinition,
+    ) -> Result<&'static [Stat], AbilityRegistryError> {
+        let component = components
+            .get(&definition.id)
+            .ok_or_else(|| AbilityRegistryError::UnknownId(definition.id.clone()))?;
+        (component.insert)(entity, definition.params.clone())
+            .map_err(|error| AbilityRegistryError::InvalidParams(definition.id.clone(), error))?;
+        Ok(component.stats)
+    }
+}
+
+pub trait RegisterAbility {
+    /// Registers a power along with the stats it reads, see [`PowerStats`].
+    fn register_power<T: Component + Default + DeserializeOwned>(
+        &mut self,
+        id: &str,
+        stats: &'static [Stat],
+    ) -> &mut Self;
+
+    fn register_side_effect<T: Component + Default + DeserializeOwned>(
+        &mut self,
+        id: &str,
+    ) -> &mut Self;
+}
+
+impl RegisterAbility for App {
+    fn register_power<T: Component + Default + DeserializeOwned>(
+        &mut self,
+        id: &str,
+        stats: &'static [Stat],
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(AbilityRegistry::default)
+            .register_power::<T>(id, stats);
+        self
+    }
+
+    fn register_side_effect<T: Component + Default + DeserializeOwned>(
+        &mut self,
+        id: &str,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(AbilityRegistry::default)
+            .register_side_effect::<T>(id);
+        self
+    }
+}
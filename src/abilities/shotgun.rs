@@ -2,68 +2,101 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 use bevy_kira_audio::{Audio, AudioControl};
-use bevy_rapier2d::prelude::{ActiveEvents, Collider, CollisionEvent, RigidBody, Velocity};
+use bevy_rapier2d::prelude::{ActiveEvents, Collider, RigidBody, Sensor, Velocity};
+use serde::Deserialize;
 
 use crate::{
-    assets::GameAssets, health::DamageEvent, lifetime::Lifetime, mouse_position::MousePosition,
-    player::Player,
+    assets::GameAssets, lifetime::Lifetime, mouse_position::MousePosition, player::Player,
+    projectile::Projectile, status::StatusEffect, timestep::AddToStep,
 };
 
 use super::{
-    cooldown::AbilityCooldown, heat::Overheated, AbilitySet, Loadout, Power, UseAbilityEvent,
+    registry::RegisterAbility,
+    stats::{Stat, StatModifiers},
+    teleport::TeleportSet,
+    AbilityActivated, AbilitySet,
 };
 
 pub struct ShotgunPlugin;
 
 impl Plugin for ShotgunPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Bullet>()
-            .add_systems((shoot, hurt).chain().in_set(AbilitySet));
+        app.register_type::<ShotgunPower>()
+            .register_power::<ShotgunPower>("shotgun", &[Stat::Damage, Stat::ProjectileCount])
+            .add_step_system(shoot.in_set(AbilitySet).before(TeleportSet));
     }
 }
 
-const BULLET_VELOCITY: f32 = 512.0;
-const BULLET_DAMAGE: f32 = 15.0;
-const SPREAD: f32 = 5.0;
+#[derive(Component, Clone, Debug, Deserialize, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+#[serde(default)]
+pub struct ShotgunPower {
+    pub bullet_velocity: f32,
+    pub bullet_damage: f32,
+    pub bullet_count: u32,
+    /// Angle between bullets in degrees
+    pub spread: f32,
+    pub pierce: u32,
+    pub bounces: u32,
+    /// Applied to every target a bullet hits
+    pub effects: Vec<StatusEffect>,
+}
 
-#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
-#[reflect(Component, Debug)]
-pub struct Bullet;
+impl Default for ShotgunPower {
+    fn default() -> Self {
+        Self {
+            bullet_velocity: 512.0,
+            bullet_damage: 15.0,
+            bullet_count: 3,
+            spread: 5.0,
+            pierce: 0,
+            bounces: 0,
+            effects: Vec::new(),
+        }
+    }
+}
 
 fn shoot(
     mut commands: Commands,
-    player_transforms: Query<&Transform, With<Player>>,
-    loadouts: Query<&Loadout, Without<Overheated>>,
-    mut ability_events: EventReader<UseAbilityEvent>,
+    player_transforms: Query<(Entity, &Transform), With<Player>>,
+    mut activations: EventReader<AbilityActivated>,
     mouse_position: Res<MousePosition>,
-    powers: Query<&Power, Without<AbilityCooldown>>,
+    powers: Query<(&ShotgunPower, &StatModifiers)>,
     audio: Res<Audio>,
     assets: Res<GameAssets>,
 ) {
-    for ability in ability_events.iter() {
-        let Ok(loadout) = loadouts.get(ability.loadout) else {
-            continue;
-        };
+    for activation in activations.iter() {
+        let Ok((power, modifiers)) = powers.get(activation.ability) else { continue; };
+        let damage = modifiers.apply(Stat::Damage, power.bullet_damage);
+        let bullet_count = modifiers
+            .apply(Stat::ProjectileCount, power.bullet_count as f32)
+            .round()
+            .max(1.0) as u32;
 
-        let ability_entity = loadout.abilities[ability.ability];
-        let Ok(power) = powers.get(ability_entity) else { continue; };
-        if *power != Power::Shotgun {
-            continue;
-        }
-
-        let player_position = player_transforms.single().translation.truncate();
+        let Ok((player_id, player_transform)) = player_transforms.get_single() else { return; };
+        let player_position = player_transform.translation.truncate();
         let dir = (**mouse_position - player_position).normalize_or_zero();
 
-        for i in -1..=1 {
-            let dir = dir.rotate(Vec2::from_angle(SPREAD.to_radians() * i as f32));
+        for i in 0..bullet_count {
+            // Spread the bullets evenly on both sides of the aimed direction
+            let offset = i as f32 - (bullet_count - 1) as f32 / 2.0;
+            let dir = dir.rotate(Vec2::from_angle(power.spread.to_radians() * offset));
             commands.spawn((
-                Bullet,
+                Projectile {
+                    damage,
+                    owner: Some(player_id),
+                    pierce: power.pierce,
+                    bounces: power.bounces,
+                    effects: power.effects.clone(),
+                    ..Default::default()
+                },
                 RigidBody::Dynamic,
                 Velocity {
-                    linvel: dir * BULLET_VELOCITY,
+                    linvel: dir * power.bullet_velocity,
                     ..Default::default()
                 },
                 Collider::ball(1.0),
+                Sensor,
                 SpriteBundle {
                     sprite: Sprite {
                         color: Color::ORANGE,
@@ -82,31 +115,3 @@ fn shoot(
         }
     }
 }
-
-fn hurt(
-    mut commands: Commands,
-    mut collision_events: EventReader<CollisionEvent>,
-    bullets: Query<(), With<Bullet>>,
-    mut damage_events: EventWriter<DamageEvent>,
-) {
-    for collision in collision_events.iter() {
-        let CollisionEvent::Started(e1, e2, _) = collision else { return };
-        if bullets.get(*e1).is_ok() && bullets.get(*e2).is_err() {
-            commands.entity(*e1).despawn_recursive();
-            damage_events.send(DamageEvent {
-                damaged_id: *e2,
-                damage: BULLET_DAMAGE,
-            });
-        } else if bullets.get(*e2).is_ok() && bullets.get(*e1).is_err() {
-            commands.entity(*e2).despawn_recursive();
-            damage_events.send(DamageEvent {
-                damaged_id: *e1,
-                damage: BULLET_DAMAGE,
-            });
-        } else if bullets.get(*e2).is_ok() && bullets.get(*e1).is_ok() {
-            // do not despawn on bullet collisions
-            //commands.entity(*e1).despawn_recursive();
-            //commands.entity(*e2).despawn_recursive();
-        }
-    }
-}
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Stat>()
+            .register_type::<ModifierKind>()
+            .register_type::<StatModifier>()
+            .register_type::<StatModifiers>();
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Reflect, FromReflect)]
+pub enum Stat {
+    #[default]
+    Cooldown,
+    Heat,
+    Damage,
+    Range,
+    ProjectileCount,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Reflect, FromReflect)]
+pub enum ModifierKind {
+    #[default]
+    Additive,
+    Multiplicative,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Reflect, FromReflect)]
+pub struct StatModifier {
+    pub stat: Stat,
+    pub kind: ModifierKind,
+    pub value: f32,
+}
+
+/// The stats read by an ability's power, inserted with it. Every ability reads [`Stat::Cooldown`]
+/// and [`Stat::Heat`] besides these.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct PowerStats(pub &'static [Stat]);
+
+impl PowerStats {
+    /// Whether upgrading `stat` changes anything for the ability.
+    pub fn reads(&self, stat: Stat) -> bool {
+        matches!(stat, Stat::Cooldown | Stat::Heat) || self.0.contains(&stat)
+    }
+}
+
+/// Upgrades collected for an ability. They stay on the ability entity when its definition is hot
+/// reloaded.
+#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct StatModifiers(pub Vec<StatModifier>);
+
+impl StatModifiers {
+    /// Adds up the additive modifiers for `stat` onto `base`, then multiplies the result by the
+    /// multiplicative ones.
+    pub fn apply(&self, stat: Stat, base: f32) -> f32 {
+        let modifiers = self.0.iter().filter(|modifier| modifier.stat == stat);
+        let (added, multiplier) = modifiers.fold((0.0, 1.0), |(added, multiplier), modifier| {
+            match modifier.kind {
+                ModifierKind::Additive => (added + modifier.value, multiplier),
+                ModifierKind::Multiplicative => (added, multiplier * modifier.value),
+            }
+        });
+
+        (base + added) * multiplier
+    }
+
+    /// Product of the multiplicative modifiers for `stat`, for rates that flat per-use
+    /// modifiers make no sense for.
+    pub fn multiplier(&self, stat: Stat) -> f32 {
+        self.0
+            .iter()
+            .filter(|modifier| {
+                modifier.stat == stat && modifier.kind == ModifierKind::Multiplicative
+            })
+            .map(|modifier| modifier.value)
+            .product()
+    }
+}
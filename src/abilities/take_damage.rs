@@ -1,42 +1,58 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
-use crate::{health::DamageEvent, player::Player};
-
-use super::{
-    cooldown::AbilityCooldown, heat::Overheated, AbilitySet, Loadout, SideEffect, UseAbilityEvent,
+use crate::{
+    health::{DamageEvent, DamageType},
+    timestep::AddToStep,
 };
 
+use super::{registry::RegisterAbility, AbilityActivated, AbilitySet};
+
 pub struct TakeDamageSideEffectPlugin;
 
 impl Plugin for TakeDamageSideEffectPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(take_damage.in_set(AbilitySet));
+        app.register_type::<TakeDamage>()
+            .register_side_effect::<TakeDamage>("take_damage")
+            .add_step_system(take_damage.in_set(AbilitySet));
     }
 }
 
-const DAMAGE: f32 = 5.0;
+#[derive(Component, Clone, Debug, Deserialize, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+#[serde(default)]
+pub struct TakeDamage {
+    pub amount: f32,
+    /// Whether the damage counts as a hit, starting i-frames and hit reactions
+    pub triggers_iframes: bool,
+}
+
+impl Default for TakeDamage {
+    fn default() -> Self {
+        Self {
+            amount: 5.0,
+            triggers_iframes: true,
+        }
+    }
+}
 
 fn take_damage(
-    player: Query<Entity, With<Player>>,
-    loadouts: Query<&Loadout, Without<Overheated>>,
-    mut ability_events: EventReader<UseAbilityEvent>,
-    side_effects: Query<&SideEffect, Without<AbilityCooldown>>,
+    mut activations: EventReader<AbilityActivated>,
+    abilities: Query<&Children>,
+    side_effects: Query<&TakeDamage>,
     mut damage_events: EventWriter<DamageEvent>,
 ) {
-    for ability in ability_events.iter() {
-        let Ok(loadout) = loadouts.get(ability.loadout) else {
-            continue;
-        };
-
-        let ability_entity = loadout.abilities[ability.ability];
-        let Ok(side_effect) = side_effects.get(ability_entity) else { continue; };
-        if *side_effect != SideEffect::TakeDamage {
-            continue;
+    for activation in activations.iter() {
+        let Ok(ability_side_effects) = abilities.get(activation.ability) else { continue; };
+
+        for side_effect in side_effects.iter_many(ability_side_effects) {
+            damage_events.send(DamageEvent {
+                damaged_id: activation.loadout,
+                damage: side_effect.amount,
+                source: Some(activation.loadout),
+                damage_type: DamageType::SelfInflicted,
+                hit: side_effect.triggers_iframes,
+            })
         }
-
-        damage_events.send(DamageEvent {
-            damaged_id: player.single(),
-            damage: DAMAGE,
-        })
     }
 }
@@ -1,48 +1,59 @@
 use bevy::prelude::*;
 use bevy_kira_audio::{Audio, AudioControl};
+use serde::Deserialize;
 
-use crate::{assets::GameAssets, mouse_position::MousePosition, player::Player};
+use crate::{
+    assets::GameAssets, mouse_position::MousePosition, player::Player, timestep::AddToStep,
+};
 
 use super::{
-    cooldown::AbilityCooldown, heat::Overheated, AbilitySet, Loadout, Power, UseAbilityEvent,
+    registry::RegisterAbility,
+    stats::{Stat, StatModifiers},
+    AbilityActivated, AbilitySet,
 };
 
 pub struct TeleportPowerPlugin;
 
 impl Plugin for TeleportPowerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(handle_teleport.in_set(AbilitySet).in_set(TeleportSet));
+        app.register_type::<TeleportPower>()
+            .register_power::<TeleportPower>("teleport", &[Stat::Range])
+            .add_step_system(handle_teleport.in_set(AbilitySet).in_set(TeleportSet));
     }
 }
 
+/// Moves the player, so other abilities used the same step act from where it was before.
 #[derive(SystemSet, Clone, Debug, Default, Hash, PartialEq, Eq)]
 pub struct TeleportSet;
 
-const TELEPORT_DISTANCE: f32 = 64.0;
+#[derive(Component, Clone, Debug, Deserialize, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+#[serde(default)]
+pub struct TeleportPower {
+    pub distance: f32,
+}
+
+impl Default for TeleportPower {
+    fn default() -> Self {
+        Self { distance: 64.0 }
+    }
+}
 
 fn handle_teleport(
     mut player_transforms: Query<&mut Transform, With<Player>>,
-    loadouts: Query<&Loadout, Without<Overheated>>,
-    mut ability_events: EventReader<UseAbilityEvent>,
+    mut activations: EventReader<AbilityActivated>,
     mouse_position: Res<MousePosition>,
-    powers: Query<&Power, Without<AbilityCooldown>>,
+    powers: Query<(&TeleportPower, &StatModifiers)>,
     audio: Res<Audio>,
     assets: Res<GameAssets>,
 ) {
-    for ability in ability_events.iter() {
-        let Ok(loadout) = loadouts.get(ability.loadout) else {
-            continue;
-        };
-
-        let ability_entity = loadout.abilities[ability.ability];
-        let Ok(power) = powers.get(ability_entity) else { continue; };
-        if *power != Power::Teleport {
-            continue;
-        }
+    for activation in activations.iter() {
+        let Ok((power, modifiers)) = powers.get(activation.ability) else { continue; };
+        let distance = modifiers.apply(Stat::Range, power.distance);
 
         for mut player_transform in &mut player_transforms {
             let player_position = player_transform.translation.truncate();
-            let delta = (**mouse_position - player_position).clamp_length_max(TELEPORT_DISTANCE);
+            let delta = (**mouse_position - player_position).clamp_length_max(distance);
 
             player_transform.translation += delta.extend(0.0);
         }
@@ -3,17 +3,23 @@ use std::time::Duration;
 use bevy::prelude::*;
 use bevy_kira_audio::{Audio, AudioControl};
 use bevy_rapier2d::prelude::{Collider, RigidBody};
+use serde::Deserialize;
 
 use crate::{
     assets::GameAssets,
     lifetime::Lifetime,
     mouse_position::MousePosition,
     player::{CurrentAbility, Player},
-    state::GameState,
+    state::{GameState, OnExitRun},
+    timestep::AddToStep,
 };
 
 use super::{
-    cooldown::AbilityCooldown, heat::Overheated, AbilitySet, Loadout, Power, UseAbilityEvent,
+    cooldown::AbilityCooldown,
+    registry::RegisterAbility,
+    stats::{Stat, StatModifiers},
+    teleport::TeleportSet,
+    AbilityActivated, AbilitySet, Loadout,
 };
 
 pub struct WallPowerPlugin;
@@ -21,13 +27,16 @@ pub struct WallPowerPlugin;
 impl Plugin for WallPowerPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<IceWall>()
-            .add_systems(
+            .register_type::<IceWallPower>()
+            .register_power::<IceWallPower>("ice_wall", &[Stat::Range])
+            .add_step_systems(
                 (show_ghost, move_ghost)
                     .chain()
+                    .after(AbilitySet)
                     .in_set(OnUpdate(GameState::Playing)),
             )
-            .add_system(spawn_icewall.in_set(AbilitySet))
-            .add_system(despawn_ghosts.in_schedule(OnExit(GameState::Playing)));
+            .add_step_system(spawn_icewall.in_set(AbilitySet).before(TeleportSet))
+            .add_system(despawn_ghosts.in_schedule(OnExitRun));
     }
 }
 
@@ -35,33 +44,41 @@ impl Plugin for WallPowerPlugin {
 #[reflect(Component, Debug)]
 pub struct IceWall;
 
-const MAX_ICEWALL_CAST_DISTANCE: f32 = 64.0;
+#[derive(Component, Clone, Debug, Deserialize, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+#[serde(default)]
+pub struct IceWallPower {
+    pub max_cast_distance: f32,
+    /// Seconds the wall stays up for
+    pub lifetime: f32,
+}
+
+impl Default for IceWallPower {
+    fn default() -> Self {
+        Self {
+            max_cast_distance: 64.0,
+            lifetime: 2.0,
+        }
+    }
+}
 
 fn spawn_icewall(
     mut commands: Commands,
     player: Query<&Transform, With<Player>>,
-    loadouts: Query<&Loadout, Without<Overheated>>,
-    mut ability_events: EventReader<UseAbilityEvent>,
+    mut activations: EventReader<AbilityActivated>,
     mouse_position: Res<MousePosition>,
-    powers: Query<&Power, Without<AbilityCooldown>>,
+    powers: Query<(&IceWallPower, &StatModifiers)>,
     audio: Res<Audio>,
     assets: Res<GameAssets>,
 ) {
-    for ability in ability_events.iter() {
-        let Ok(loadout) = loadouts.get(ability.loadout) else {
-            continue;
-        };
-
-        let ability_entity = loadout.abilities[ability.ability];
-        let Ok(power) = powers.get(ability_entity) else { continue; };
-        if *power != Power::IceWall {
-            continue;
-        }
+    for activation in activations.iter() {
+        let Ok((power, modifiers)) = powers.get(activation.ability) else { continue; };
+        let max_cast_distance = modifiers.apply(Stat::Range, power.max_cast_distance);
 
-        let player_position = player.single().translation.truncate();
+        let Ok(player_transform) = player.get_single() else { return; };
+        let player_position = player_transform.translation.truncate();
 
-        let delta =
-            (**mouse_position - player_position).clamp_length_max(MAX_ICEWALL_CAST_DISTANCE);
+        let delta = (**mouse_position - player_position).clamp_length_max(max_cast_distance);
         let rotation = delta.perp().angle_between(Vec2::X);
 
         let transform = Transform::from_translation((player_position + delta).extend(0.0))
@@ -80,7 +97,7 @@ fn spawn_icewall(
             },
             Collider::cuboid(32.0, 4.0),
             RigidBody::Fixed,
-            Lifetime::new(Duration::from_secs(2)),
+            Lifetime::new(Duration::from_secs_f32(power.lifetime)),
         ));
 
         audio.play(assets.ice_wall_sound.clone());
@@ -100,13 +117,14 @@ fn despawn_ghosts(mut commands: Commands, ghosts: Query<Entity, With<IceWallGhos
 fn show_ghost(
     mut commands: Commands,
     player: Query<(&CurrentAbility, &Loadout), (With<Player>, Changed<CurrentAbility>)>,
-    powers: Query<&Power, Without<AbilityCooldown>>,
+    powers: Query<(), (With<IceWallPower>, Without<AbilityCooldown>)>,
     ghosts: Query<Entity, With<IceWallGhost>>,
 ) {
-    let Ok((current_ability, loadout)) = player.get_single() else { return;};
+    let Ok((current_ability, loadout)) = player.get_single() else { return; };
+    // Slots past the end of the loadout are empty
+    let ability = loadout.abilities.get(current_ability.0);
 
-    let Ok(power) = powers.get(loadout.abilities[current_ability.0]) else { return; };
-    if *power == Power::IceWall {
+    if ability.is_some_and(|&ability| powers.get(ability).is_ok()) {
         commands.spawn((
             IceWallGhost,
             SpriteBundle {
@@ -126,16 +144,19 @@ fn show_ghost(
 }
 
 fn move_ghost(
-    player: Query<&Transform, With<Player>>,
+    player: Query<(&Transform, &CurrentAbility, &Loadout), With<Player>>,
+    powers: Query<(&IceWallPower, &StatModifiers)>,
     mouse_position: Res<MousePosition>,
     mut ghosts: Query<&mut Transform, (With<IceWallGhost>, Without<Player>)>,
 ) {
     for mut ghost_transform in &mut ghosts {
-        let Ok(player_transform) = player.get_single() else { return; };
+        let Ok((player_transform, current_ability, loadout)) = player.get_single() else { return; };
+        let Some(&ability) = loadout.abilities.get(current_ability.0) else { return; };
+        let Ok((power, modifiers)) = powers.get(ability) else { return; };
+        let max_cast_distance = modifiers.apply(Stat::Range, power.max_cast_distance);
         let player_position = player_transform.translation.truncate();
 
-        let delta =
-            (**mouse_position - player_position).clamp_length_max(MAX_ICEWALL_CAST_DISTANCE);
+        let delta = (**mouse_position - player_position).clamp_length_max(max_cast_distance);
         let rotation = delta.perp().angle_between(Vec2::X);
 
         let transform = Transform::from_translation((player_position + delta).extend(0.0))
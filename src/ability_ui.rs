@@ -1,19 +1,35 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 
 use crate::{
-    abilities::{cooldown::AbilityCooldown, Loadout},
+    abilities::{
+        activation::Charging, cooldown::AbilityCooldown, definition::AbilityDefinition,
+        AbilityRejected, Loadout, RejectionReason,
+    },
     assets::GameAssets,
     player::{CurrentAbility, Player},
-    state::GameState,
+    state::{GameState, OnEnterRun, OnExitRun},
+    timestep::FrameEvents,
 };
 
 pub struct AbilityUiPlugin;
 
 impl Plugin for AbilityUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(spawn_ability_ui.in_schedule(OnEnter(GameState::Playing)))
-            .add_systems((move_highlight, set_highlight_width).in_set(OnUpdate(GameState::Playing)))
-            .add_system(despawn_ability_ui.in_schedule(OnExit(GameState::Playing)));
+        app.add_system(spawn_ability_ui.in_schedule(OnEnterRun))
+            .add_systems(
+                (
+                    move_highlight,
+                    set_highlight_width,
+                    set_charge_bar_width,
+                    set_slot_icons,
+                    show_rejection_message,
+                    fade_message,
+                )
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(despawn_ability_ui.in_schedule(OnExitRun));
     }
 }
 
@@ -23,48 +39,32 @@ pub struct AbilityUi;
 #[derive(Component, Default, Debug)]
 struct AbilityUiHighlight;
 
+#[derive(Component, Default, Debug)]
+struct AbilityUiChargeBar;
+
+#[derive(Component, Debug)]
+struct AbilityUiMessage(pub Timer);
+
 #[derive(Resource)]
 struct AbilitySlots {
     pub slots: [Entity; 4],
 }
 
 const SELECTOR_WIDTH: f32 = 64.0;
+const MESSAGE_COLOR: Color = Color::ORANGE_RED;
 
 fn spawn_ability_ui(mut commands: Commands, assets: Res<GameAssets>) {
-    let style = Style {
-        size: Size::new(Val::Px(64.0), Val::Px(64.0)),
-        ..Default::default()
-    };
-    let slots = [
-        commands
-            .spawn(ImageBundle {
-                style: style.clone(),
-                image: UiImage::new(assets.teleport_slot.clone()),
-                ..Default::default()
-            })
-            .id(),
-        commands
-            .spawn(ImageBundle {
-                style: style.clone(),
-                image: UiImage::new(assets.fireball_slot.clone()),
-                ..Default::default()
-            })
-            .id(),
+    let slots = [(); 4].map(|_| {
         commands
             .spawn(ImageBundle {
-                style: style.clone(),
-                image: UiImage::new(assets.ice_wall_slot.clone()),
-                ..Default::default()
-            })
-            .id(),
-        commands
-            .spawn(ImageBundle {
-                style,
-                image: UiImage::new(assets.shotgun_slot.clone()),
+                style: Style {
+                    size: Size::new(Val::Px(64.0), Val::Px(64.0)),
+                    ..Default::default()
+                },
                 ..Default::default()
             })
-            .id(),
-    ];
+            .id()
+    });
     commands
         .spawn((
             AbilityUi,
@@ -82,6 +82,26 @@ fn spawn_ability_ui(mut commands: Commands, assets: Res<GameAssets>) {
 
     commands.insert_resource(AbilitySlots { slots });
 
+    commands.spawn((
+        AbilityUiMessage(Timer::new(Duration::from_secs_f32(0.8), TimerMode::Once)),
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font_normal.clone(),
+                    font_size: 32.0,
+                    color: MESSAGE_COLOR,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::new(Val::Px(20.0), Val::Auto, Val::Px(96.0), Val::Auto),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    ));
+
     commands
         .spawn((
             AbilityUiHighlight,
@@ -106,11 +126,33 @@ fn spawn_ability_ui(mut commands: Commands, assets: Res<GameAssets>) {
                 ..Default::default()
             });
         });
+
+    commands.spawn((
+        AbilityUiChargeBar,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::new(Val::Px(0.0), Val::Auto, Val::Px(74.0), Val::Auto),
+                size: Size::new(Val::Px(0.0), Val::Px(4.0)),
+                ..Default::default()
+            },
+            background_color: Color::ORANGE.into(),
+            ..Default::default()
+        },
+    ));
 }
 
 fn despawn_ability_ui(
     mut commands: Commands,
-    query: Query<Entity, Or<(With<AbilityUi>, With<AbilityUiHighlight>)>>,
+    query: Query<
+        Entity,
+        Or<(
+            With<AbilityUi>,
+            With<AbilityUiHighlight>,
+            With<AbilityUiChargeBar>,
+            With<AbilityUiMessage>,
+        )>,
+    >,
 ) {
     for e in &query {
         commands.entity(e).despawn_recursive();
@@ -119,14 +161,26 @@ fn despawn_ability_ui(
 
 fn move_highlight(
     mut commands: Commands,
-    highlight: Query<Entity, With<AbilityUiHighlight>>,
+    highlight: Query<Entity, Or<(With<AbilityUiHighlight>, With<AbilityUiChargeBar>)>>,
     selected_ability: Query<&CurrentAbility, Changed<CurrentAbility>>,
     slots: Res<AbilitySlots>,
 ) {
     for ability in &selected_ability {
-        let id = highlight.single();
-        commands.entity(id).remove_parent();
-        commands.entity(slots.slots[ability.0]).add_child(id);
+        for id in &highlight {
+            commands.entity(id).remove_parent();
+            commands.entity(slots.slots[ability.0]).add_child(id);
+        }
+    }
+}
+
+fn set_charge_bar_width(
+    charging: Query<Option<&Charging>, With<Player>>,
+    mut charge_bar: Query<&mut Style, With<AbilityUiChargeBar>>,
+) {
+    let Ok(charging) = charging.get_single() else { return; };
+    for mut style in &mut charge_bar {
+        let percent = charging.map_or(0.0, Charging::percent);
+        style.size.width = Val::Px(SELECTOR_WIDTH * percent);
     }
 }
 
@@ -140,7 +194,7 @@ fn set_highlight_width(
         let mut style = highlight.single_mut();
 
         let Ok(loadout) = loadout.get_single() else { continue; };
-        let ability_id = loadout.abilities[ability.0];
+        let Some(&ability_id) = loadout.abilities.get(ability.0) else { continue; };
         let cooldown = cooldown.get(ability_id).unwrap();
 
         if let Some(cooldown) = cooldown {
@@ -150,3 +204,53 @@ fn set_highlight_width(
         }
     }
 }
+
+fn set_slot_icons(
+    loadouts: Query<&Loadout, (With<Player>, Changed<Loadout>)>,
+    abilities: Query<&Handle<AbilityDefinition>>,
+    definitions: Res<Assets<AbilityDefinition>>,
+    slots: Res<AbilitySlots>,
+    mut images: Query<&mut UiImage>,
+    asset_server: Res<AssetServer>,
+) {
+    for loadout in &loadouts {
+        for (&slot, &ability) in slots.slots.iter().zip(&loadout.abilities) {
+            let Ok(handle) = abilities.get(ability) else { continue; };
+            let Some(definition) = definitions.get(handle) else { continue; };
+            let Ok(mut image) = images.get_mut(slot) else { continue; };
+
+            image.texture = asset_server.load(definition.icon.as_str());
+        }
+    }
+}
+
+fn show_rejection_message(
+    rejected_events: Res<FrameEvents<AbilityRejected>>,
+    player: Query<Entity, With<Player>>,
+    mut messages: Query<(&mut AbilityUiMessage, &mut Text)>,
+) {
+    for rejection in rejected_events.iter() {
+        if player.get(rejection.loadout).is_err() {
+            continue;
+        }
+        let message = match rejection.reason {
+            RejectionReason::Cooldown => "Cooling down",
+            RejectionReason::Overheated => "Overheated!",
+            RejectionReason::Incapacitated => "Stunned!",
+            RejectionReason::NotLoaded => "Not ready",
+            RejectionReason::InvalidSlot => continue,
+        };
+
+        for (mut timer, mut text) in &mut messages {
+            timer.0.reset();
+            text.sections[0].value = message.to_owned();
+        }
+    }
+}
+
+fn fade_message(mut messages: Query<(&mut AbilityUiMessage, &mut Text)>, time: Res<Time>) {
+    for (mut timer, mut text) in &mut messages {
+        timer.0.tick(time.delta());
+        text.sections[0].style.color = MESSAGE_COLOR.with_a(timer.0.percent_left());
+    }
+}
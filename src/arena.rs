@@ -0,0 +1,277 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use bevy_ecs_tilemap::prelude::*;
+use bevy_rapier2d::prelude::{Collider, RigidBody};
+use serde::Deserialize;
+
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ArenaLayout>()
+            .init_asset_loader::<ArenaLayoutLoader>();
+    }
+}
+
+/// Width and height of a tile.
+pub const TILE_SIZE: f32 = 16.0;
+
+const FLOOR_TEXTURE: u32 = 0;
+const WALL_TEXTURE: u32 = 1;
+
+/// The floor and walls of a room, spawned from an [`ArenaLayout`].
+#[derive(Component, Clone, Default, Debug)]
+pub struct Arena {
+    pub layout: Handle<ArenaLayout>,
+    /// Smallest rectangle around every floor tile
+    pub floor: Rect,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Floor,
+    /// Floor enemies can spawn on
+    Spawn,
+    Wall,
+}
+
+impl Tile {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            ' ' => Some(Self::Empty),
+            '.' => Some(Self::Floor),
+            'x' => Some(Self::Spawn),
+            '#' => Some(Self::Wall),
+            _ => None,
+        }
+    }
+}
+
+/// The tiles of an arena, loaded from `.arena.ron` files. Each string is a row of tiles from the
+/// top, with `#` for walls, `.` for floor, `x` for floor enemies spawn on, and spaces for
+/// nothing. Layouts are centered on the origin, where the player is put when the arena changes.
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "6b1f8f43-2a4c-4f6e-9d52-0c8e3a7d51b2"]
+pub struct ArenaLayout {
+    pub tiles: Vec<String>,
+}
+
+impl ArenaLayout {
+    /// Width and height in tiles, rows shorter than the longest are padded with nothing.
+    pub fn size(&self) -> UVec2 {
+        let width = self
+            .tiles
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        UVec2::new(width as u32, self.tiles.len() as u32)
+    }
+
+    /// The tile in column `x` and row `y`, counting from the top left.
+    pub fn tile(&self, x: u32, y: u32) -> Tile {
+        self.tiles
+            .get(y as usize)
+            .and_then(|row| row.chars().nth(x as usize))
+            .and_then(Tile::from_char)
+            .unwrap_or(Tile::Empty)
+    }
+
+    /// Center of the tile in column `x` and row `y`, in world units.
+    pub fn tile_center(&self, x: u32, y: u32) -> Vec2 {
+        let half_size = (self.size().as_vec2() - 1.0) / 2.0;
+        Vec2::new(x as f32 - half_size.x, half_size.y - y as f32) * TILE_SIZE
+    }
+
+    fn tiles(&self) -> impl Iterator<Item = (u32, u32, Tile)> + '_ {
+        let size = self.size();
+        (0..size.y).flat_map(move |y| (0..size.x).map(move |x| (x, y, self.tile(x, y))))
+    }
+
+    /// Where enemies can spawn, the centers of the spawn tiles.
+    pub fn spawn_points(&self) -> Vec<Vec2> {
+        self.tiles()
+            .filter(|&(_, _, tile)| tile == Tile::Spawn)
+            .map(|(x, y, _)| self.tile_center(x, y))
+            .collect()
+    }
+
+    /// Smallest rectangle around every floor tile, in world units.
+    pub fn floor_bounds(&self) -> Rect {
+        self.tiles()
+            .filter(|&(_, _, tile)| matches!(tile, Tile::Floor | Tile::Spawn))
+            .map(|(x, y, _)| Rect::from_center_size(self.tile_center(x, y), Vec2::splat(TILE_SIZE)))
+            .reduce(|bounds, tile| bounds.union(tile))
+            .unwrap_or_default()
+    }
+
+    /// The wall tiles merged into as few rectangles as it takes to cover them, in world units.
+    /// Each is grown as wide as it goes from its top left tile, then as tall as that width allows.
+    pub fn walls(&self) -> Vec<Rect> {
+        let size = self.size();
+        let mut merged = vec![false; (size.x * size.y) as usize];
+        let free = |merged: &[bool], x: u32, y: u32| {
+            self.tile(x, y) == Tile::Wall && !merged[(y * size.x + x) as usize]
+        };
+
+        let mut walls = Vec::new();
+        for (x, y, _) in self.tiles() {
+            if !free(&merged, x, y) {
+                continue;
+            }
+            let mut width = 1;
+            while x + width < size.x && free(&merged, x + width, y) {
+                width += 1;
+            }
+            let mut height = 1;
+            while y + height < size.y && (x..x + width).all(|x| free(&merged, x, y + height)) {
+                height += 1;
+            }
+
+            for y in y..y + height {
+                for x in x..x + width {
+                    merged[(y * size.x + x) as usize] = true;
+                }
+            }
+            let half_tile = Vec2::new(TILE_SIZE, -TILE_SIZE) / 2.0;
+            walls.push(Rect::from_corners(
+                self.tile_center(x, y) - half_tile,
+                self.tile_center(x + width - 1, y + height - 1) + half_tile,
+            ));
+        }
+        walls
+    }
+}
+
+#[derive(Default)]
+struct ArenaLayoutLoader;
+
+impl AssetLoader for ArenaLayoutLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let layout = ron::de::from_bytes::<ArenaLayout>(bytes)?;
+            let all_tiles = layout.tiles.iter().flat_map(|row| row.chars());
+            if let Some(c) = all_tiles.clone().find(|&c| Tile::from_char(c).is_none()) {
+                return Err(bevy::asset::Error::msg(format!("unknown tile {c:?}")));
+            }
+            // Rooms without spawns would never have enemies, and colliders need a shape
+            for (c, name) in [('x', "spawn"), ('#', "wall")] {
+                if !all_tiles.clone().any(|tile| tile == c) {
+                    return Err(bevy::asset::Error::msg(format!("no {name} tiles")));
+                }
+            }
+            load_context.set_default_asset(LoadedAsset::new(layout));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["arena.ron"]
+    }
+}
+
+/// Spawns the floor and wall tilemaps of the layout, under an [`Arena`] with a collider for each
+/// merged rectangle of walls.
+pub fn spawn_arena(
+    commands: &mut Commands,
+    handle: Handle<ArenaLayout>,
+    layout: &ArenaLayout,
+    texture: Handle<Image>,
+) -> Entity {
+    let walls = layout
+        .walls()
+        .into_iter()
+        .map(|wall| {
+            let half_size = wall.half_size();
+            (
+                wall.center(),
+                0.0,
+                Collider::cuboid(half_size.x, half_size.y),
+            )
+        })
+        .collect();
+    let arena = commands
+        .spawn((
+            Arena {
+                layout: handle,
+                floor: layout.floor_bounds(),
+            },
+            RigidBody::Fixed,
+            Collider::compound(walls),
+            SpatialBundle::default(),
+        ))
+        .id();
+
+    // Below everything else, ice walls included
+    let floor = spawn_layer(commands, layout, texture.clone(), -1.0, |tile| {
+        matches!(tile, Tile::Floor | Tile::Spawn).then_some(FLOOR_TEXTURE)
+    });
+    let walls = spawn_layer(commands, layout, texture, -0.5, |tile| {
+        (tile == Tile::Wall).then_some(WALL_TEXTURE)
+    });
+    commands.entity(arena).push_children(&[floor, walls]);
+    arena
+}
+
+/// Spawns a tilemap with a tile wherever `texture_index` gives one.
+fn spawn_layer(
+    commands: &mut Commands,
+    layout: &ArenaLayout,
+    texture: Handle<Image>,
+    z: f32,
+    texture_index: impl Fn(Tile) -> Option<u32>,
+) -> Entity {
+    let size = layout.size();
+    let map_size = TilemapSize {
+        x: size.x,
+        y: size.y,
+    };
+    let tile_size = TilemapTileSize {
+        x: TILE_SIZE,
+        y: TILE_SIZE,
+    };
+    let grid_size = tile_size.into();
+    let map_type = TilemapType::Square;
+
+    let tilemap = commands.spawn_empty().id();
+    let mut storage = TileStorage::empty(map_size);
+    for (x, y, tile) in layout.tiles() {
+        let Some(index) = texture_index(tile) else { continue; };
+        // Tile positions count from the bottom
+        let position = TilePos {
+            x,
+            y: size.y - 1 - y,
+        };
+        let tile = commands
+            .spawn(TileBundle {
+                position,
+                tilemap_id: TilemapId(tilemap),
+                texture_index: TileTextureIndex(index),
+                ..Default::default()
+            })
+            .id();
+        storage.set(&position, tile);
+        commands.entity(tilemap).add_child(tile);
+    }
+
+    commands.entity(tilemap).insert(TilemapBundle {
+        grid_size,
+        map_type,
+        size: map_size,
+        storage,
+        texture: TilemapTexture::Single(texture),
+        tile_size,
+        transform: get_tilemap_center_transform(&map_size, &grid_size, &map_type, z),
+        ..Default::default()
+    });
+    tilemap
+}
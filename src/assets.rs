@@ -1,7 +1,7 @@
 use bevy::{asset::LoadState, prelude::*};
 use bevy_kira_audio::AudioSource;
 
-use crate::state::GameState;
+use crate::{abilities::definition::AbilityDefinition, arena::ArenaLayout, state::GameState};
 
 pub struct AssetsPlugin;
 
@@ -12,6 +12,17 @@ impl Plugin for AssetsPlugin {
     }
 }
 
+/// Only loads the ability definitions and arena layouts the game logic needs, every image, sound
+/// and font is left as a default handle. Used instead of [`AssetsPlugin`] when running headless.
+pub struct StubAssetsPlugin;
+
+impl Plugin for StubAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(start_loading_stub_assets)
+            .add_system(on_stub_assets_loaded.run_if(in_state(GameState::Loading)));
+    }
+}
+
 #[derive(Resource, Clone, Default, Debug, Reflect, FromReflect)]
 #[reflect(Resource, Default, Debug)]
 pub struct GameAssets {
@@ -22,12 +33,21 @@ pub struct GameAssets {
     pub shotgun_shot: Handle<AudioSource>,
     pub ice_wall_sound: Handle<AudioSource>,
     pub teleport_sound: Handle<AudioSource>,
+    pub dud_sound: Handle<AudioSource>,
+    pub hurt_sound: Handle<AudioSource>,
     pub font_normal: Handle<Font>,
     pub font_italic: Handle<Font>,
     pub fireball_slot: Handle<Image>,
     pub ice_wall_slot: Handle<Image>,
     pub shotgun_slot: Handle<Image>,
     pub teleport_slot: Handle<Image>,
+    pub teleport_ability: Handle<AbilityDefinition>,
+    pub fireball_ability: Handle<AbilityDefinition>,
+    pub ice_wall_ability: Handle<AbilityDefinition>,
+    pub shotgun_ability: Handle<AbilityDefinition>,
+    pub tiles: Handle<Image>,
+    /// Layouts rooms are picked from, the first room always gets the first one
+    pub arenas: Vec<Handle<ArenaLayout>>,
 }
 
 fn start_loading_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
@@ -39,15 +59,59 @@ fn start_loading_assets(mut commands: Commands, asset_server: Res<AssetServer>)
         shotgun_shot: asset_server.load("shotgun.wav"),
         ice_wall_sound: asset_server.load("ice_wall.wav"),
         teleport_sound: asset_server.load("teleport.wav"),
+        dud_sound: asset_server.load("dud.wav"),
+        hurt_sound: asset_server.load("hurt.wav"),
         font_normal: asset_server.load("font/ChangaOne-Regular.ttf"),
         font_italic: asset_server.load("font/ChangaOne-Italic.ttf"),
         fireball_slot: asset_server.load("fireball_slot.png"),
         ice_wall_slot: asset_server.load("ice_wall_slot.png"),
         shotgun_slot: asset_server.load("shotgun_slot.png"),
         teleport_slot: asset_server.load("teleport_slot.png"),
+        teleport_ability: asset_server.load("abilities/teleport.ability.ron"),
+        fireball_ability: asset_server.load("abilities/fireball.ability.ron"),
+        ice_wall_ability: asset_server.load("abilities/ice_wall.ability.ron"),
+        shotgun_ability: asset_server.load("abilities/shotgun.ability.ron"),
+        tiles: asset_server.load("tiles.png"),
+        arenas: load_arenas(&asset_server),
     });
 }
 
+fn start_loading_stub_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameAssets {
+        teleport_ability: asset_server.load("abilities/teleport.ability.ron"),
+        fireball_ability: asset_server.load("abilities/fireball.ability.ron"),
+        ice_wall_ability: asset_server.load("abilities/ice_wall.ability.ron"),
+        shotgun_ability: asset_server.load("abilities/shotgun.ability.ron"),
+        arenas: load_arenas(&asset_server),
+        ..Default::default()
+    });
+}
+
+fn load_arenas(asset_server: &AssetServer) -> Vec<Handle<ArenaLayout>> {
+    ["open", "pillars", "corridors", "cover"]
+        .into_iter()
+        .map(|name| asset_server.load(format!("arenas/{name}.arena.ron")))
+        .collect()
+}
+
+fn on_stub_assets_loaded(
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let assets = [
+        assets.teleport_ability.id(),
+        assets.fireball_ability.id(),
+        assets.ice_wall_ability.id(),
+        assets.shotgun_ability.id(),
+    ]
+    .into_iter()
+    .chain(assets.arenas.iter().map(Handle::id));
+    if asset_server.get_group_load_state(assets) == LoadState::Loaded {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
 fn on_assets_loaded(
     asset_server: Res<AssetServer>,
     assets: Res<GameAssets>,
@@ -63,7 +127,14 @@ fn on_assets_loaded(
         assets.ice_wall_slot.id(),
         assets.shotgun_slot.id(),
         assets.teleport_slot.id(),
-    ];
+        assets.teleport_ability.id(),
+        assets.fireball_ability.id(),
+        assets.ice_wall_ability.id(),
+        assets.shotgun_ability.id(),
+        assets.tiles.id(),
+    ]
+    .into_iter()
+    .chain(assets.arenas.iter().map(Handle::id));
     if asset_server.get_group_load_state(assets) == LoadState::Loaded {
         next_state.set(GameState::MainMenu);
         info!("Loaded all assets");
@@ -1,32 +1,33 @@
 use bevy::prelude::*;
 use bevy_kira_audio::{Audio, AudioControl};
 
+use crate::settings::Settings;
+
 pub struct AudioPlugin;
 
 impl Plugin for AudioPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(bevy_kira_audio::AudioPlugin)
-            .init_resource::<Volume>()
-            .add_system(set_volume);
+            .add_systems((adjust_volume, set_volume).chain());
     }
 }
 
-#[derive(Resource)]
-struct Volume(f64);
+const VOLUME_STEP: f32 = 0.05;
 
-impl FromWorld for Volume {
-    fn from_world(world: &mut World) -> Self {
-        world.resource_mut::<Audio>().set_volume(1.0);
-        Self(1.0)
-    }
+fn adjust_volume(keys: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    let step = if keys.just_pressed(KeyCode::Plus) {
+        VOLUME_STEP
+    } else if keys.just_pressed(KeyCode::Minus) {
+        -VOLUME_STEP
+    } else {
+        return;
+    };
+    settings.master_volume = (settings.master_volume + step).clamp(0.0, 1.0);
 }
 
-fn set_volume(keys: Res<Input<KeyCode>>, audio: Res<Audio>, mut volume: ResMut<Volume>) {
-    if keys.just_pressed(KeyCode::Plus) {
-        volume.0 += 0.05;
-        audio.set_volume(volume.0);
-    } else if keys.just_pressed(KeyCode::Minus) {
-        volume.0 -= 0.05;
-        audio.set_volume(volume.0);
+fn set_volume(settings: Res<Settings>, audio: Res<Audio>) {
+    if !settings.is_changed() {
+        return;
     }
+    audio.set_volume(settings.effective_sfx_volume() as f64);
 }
@@ -0,0 +1,367 @@
+//! Plays many runs headless with the [`Bot`] and reports how they went, to tune the room
+//! difficulty and heat against data instead of by feel.
+//!
+//! `cargo run --release --features testing --bin simulate -- --runs 50 --overheat-threshold 120`
+
+use std::{collections::BTreeMap, path::PathBuf, process::ExitCode, str::FromStr};
+
+use bevy::{
+    ecs::event::{Event, ManualEventReader},
+    prelude::*,
+    utils::HashMap,
+};
+use labrats::{
+    abilities::{
+        definition::AbilityDefinition,
+        heat::{HeatConfig, Overheated},
+        AbilityActivated,
+    },
+    bot::{Bot, BotPlugin},
+    draft::DraftChoice,
+    enemy::Enemy,
+    health::DamageDealtEvent,
+    room::RoomClearedEvent,
+    room_manager::{CurrentRoom, RoomDifficulty, RunTime},
+    seed::RunSeed,
+    state::GameState,
+    testing::TestHarness,
+    timestep::TIMESTEP,
+};
+
+const USAGE: &str = "\
+Usage: simulate [options]
+
+  --runs <n>                  Runs to play, at least 1, 20 by default
+  --seed <n>                  Run i is played with seed n + i, 0 by default
+  --max-minutes <n>           Game time after which a run is given up on, 10 by default
+  --replays <dir>             Saves the replay of every run there, to watch with --replay
+
+Bot:
+  --kite-distance <n>         Distance kept from enemies
+  --attack-range <n>          Enemies further than this aren't attacked
+  --panic-distance <n>        Teleports away when an enemy gets this close
+  --heat-margin <n>           Part of the overheat threshold the bot stays below
+  --min-health <n>            Abilities that hurt the bot aren't used below this much health
+  --hold-time <n>             Seconds charged and channeled abilities are held
+  --draft <skip|offer:slot>   Choice made in every draft
+
+Balance:
+  --overheat-threshold <n>
+  --heat-removal-rate <n>
+  --difficulty-base <n>       Budget of the rooms after the first
+  --difficulty-per-room <n>   Budget added for every room cleared
+  --difficulty-spread <n>     Random budget added or removed, at most this much
+  --bomb-cost <n>
+  --cat-cost <n>
+";
+
+struct Options {
+    runs: u64,
+    seed: u64,
+    max_minutes: f32,
+    replays: Option<PathBuf>,
+    bot: Bot,
+    heat: HeatConfig,
+    difficulty: RoomDifficulty,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            runs: 20,
+            seed: 0,
+            max_minutes: 10.0,
+            replays: None,
+            bot: Bot::default(),
+            heat: HeatConfig::default(),
+            difficulty: RoomDifficulty::default(),
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--runs" => options.runs = parse(&arg, &value()?)?,
+                "--seed" => options.seed = parse(&arg, &value()?)?,
+                "--max-minutes" => options.max_minutes = parse(&arg, &value()?)?,
+                "--replays" => options.replays = Some(PathBuf::from(value()?)),
+                "--kite-distance" => options.bot.kite_distance = parse(&arg, &value()?)?,
+                "--attack-range" => options.bot.attack_range = parse(&arg, &value()?)?,
+                "--panic-distance" => options.bot.panic_distance = parse(&arg, &value()?)?,
+                "--heat-margin" => options.bot.heat_margin = parse(&arg, &value()?)?,
+                "--min-health" => options.bot.min_health = parse(&arg, &value()?)?,
+                "--hold-time" => options.bot.hold_time = parse(&arg, &value()?)?,
+                "--draft" => options.bot.draft = parse_draft(&value()?)?,
+                "--overheat-threshold" => options.heat.overheat_threshold = parse(&arg, &value()?)?,
+                "--heat-removal-rate" => options.heat.removal_rate = parse(&arg, &value()?)?,
+                "--difficulty-base" => options.difficulty.base = parse(&arg, &value()?)?,
+                "--difficulty-per-room" => options.difficulty.per_room = parse(&arg, &value()?)?,
+                "--difficulty-spread" => options.difficulty.spread = parse(&arg, &value()?)?,
+                "--bomb-cost" => set_cost(&mut options.difficulty, Enemy::Bomb, &arg, &value()?)?,
+                "--cat-cost" => set_cost(&mut options.difficulty, Enemy::Cat, &arg, &value()?)?,
+                _ => return Err(format!("unknown option {arg}")),
+            }
+        }
+        // The report averages over the runs
+        if options.runs == 0 {
+            return Err("--runs needs to be at least 1".to_string());
+        }
+        Ok(options)
+    }
+}
+
+fn parse<T: FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {arg}: {value}"))
+}
+
+fn parse_draft(value: &str) -> Result<DraftChoice, String> {
+    if value == "skip" {
+        return Ok(DraftChoice::Skip);
+    }
+    let (offer, slot) = value
+        .split_once(':')
+        .ok_or_else(|| format!("invalid draft choice {value}, expected skip or offer:slot"))?;
+    Ok(DraftChoice::Take {
+        offer: parse("--draft", offer)?,
+        slot: parse("--draft", slot)?,
+    })
+}
+
+fn set_cost(
+    difficulty: &mut RoomDifficulty,
+    enemy: Enemy,
+    arg: &str,
+    value: &str,
+) -> Result<(), String> {
+    let cost = parse(arg, value)?;
+    for (option, option_cost) in &mut difficulty.enemy_costs {
+        if *option == enemy {
+            *option_cost = cost;
+        }
+    }
+    Ok(())
+}
+
+/// What happened in a single run.
+#[derive(Default, Debug)]
+struct RunStats {
+    seed: RunSeed,
+    rooms_cleared: u32,
+    died: bool,
+    time: f32,
+    /// Damage the player took by what dealt it
+    damage_taken: BTreeMap<String, f32>,
+    /// Activations by ability name
+    ability_uses: BTreeMap<String, u32>,
+    overheats: u32,
+    /// Seconds it took to clear each room, in order
+    clear_times: Vec<f32>,
+}
+
+/// Reads the events of a type sent since the last call.
+struct Reader<E: Event>(ManualEventReader<E>);
+
+impl<E: Event + Clone> Reader<E> {
+    fn new() -> Self {
+        Self(ManualEventReader::default())
+    }
+
+    fn read(&mut self, harness: &TestHarness) -> Vec<E> {
+        let events = harness.app.world.resource::<Events<E>>();
+        self.0.iter(events).cloned().collect()
+    }
+}
+
+fn simulate(harness: &mut TestHarness, options: &Options, seed: u64) -> RunStats {
+    let mut damage_events = Reader::<DamageDealtEvent>::new();
+    let mut activated_events = Reader::<AbilityActivated>::new();
+    let mut cleared_events = Reader::<RoomClearedEvent>::new();
+    // Skip whatever is left over from the last run
+    damage_events.read(harness);
+    activated_events.read(harness);
+    cleared_events.read(harness);
+
+    harness.start_run_with_seed(seed);
+    let player = harness.player();
+    let mut stats = RunStats {
+        seed: RunSeed(seed),
+        ..Default::default()
+    };
+    // Enemies are often gone by the time their damage is dealt, like bombs that blew up
+    let mut enemy_kinds = HashMap::new();
+    let mut was_overheated = false;
+    let mut room_started = 0.0;
+
+    let max_steps = (options.max_minutes * 60.0 / TIMESTEP.as_secs_f32()) as u64;
+    for _ in 0..max_steps {
+        for enemy in harness.enemies() {
+            if let Some(&kind) = harness.get::<Enemy>(enemy) {
+                enemy_kinds.insert(enemy, kind);
+            }
+        }
+
+        harness.step();
+
+        for event in damage_events.read(harness) {
+            if event.target != player {
+                continue;
+            }
+            let source = match event.source {
+                Some(source) if source == player => "self".to_string(),
+                Some(source) if enemy_kinds.contains_key(&source) => {
+                    format!("{:?}", enemy_kinds[&source]).to_lowercase()
+                }
+                _ => format!("{:?}", event.damage_type).to_lowercase(),
+            };
+            *stats.damage_taken.entry(source).or_default() += event.damage;
+        }
+        for event in activated_events.read(harness) {
+            let name = harness
+                .get::<Handle<AbilityDefinition>>(event.ability)
+                .and_then(|handle| {
+                    let definitions = harness.app.world.resource::<Assets<AbilityDefinition>>();
+                    definitions.get(handle)
+                })
+                .map_or_else(
+                    || "unknown".to_string(),
+                    |definition| definition.name.clone(),
+                );
+            *stats.ability_uses.entry(name).or_default() += 1;
+        }
+        let run_time = harness.app.world.resource::<RunTime>().elapsed_secs();
+        for _ in cleared_events.read(harness) {
+            stats.clear_times.push(run_time - room_started);
+            room_started = run_time;
+        }
+
+        let overheated = harness.exists(player) && harness.get::<Overheated>(player).is_some();
+        if overheated && !was_overheated {
+            stats.overheats += 1;
+        }
+        was_overheated = overheated;
+
+        if !harness.state().in_run() {
+            break;
+        }
+    }
+
+    stats.died = harness.state() == GameState::DeathScreen;
+    stats.rooms_cleared = harness.app.world.resource::<CurrentRoom>().0;
+    stats.time = harness.app.world.resource::<RunTime>().elapsed_secs();
+    if let Some(dir) = &options.replays {
+        harness
+            .recording()
+            .save(&dir.join(format!("{}.ron", stats.seed)));
+    }
+    harness.set_state(GameState::MainMenu);
+    stats
+}
+
+fn report(runs: &[RunStats]) {
+    let count = runs.len() as f32;
+    let per_run = |total: f32| total / count;
+
+    let deaths = runs.iter().filter(|run| run.died).count();
+    let rooms: Vec<_> = runs.iter().map(|run| run.rooms_cleared).collect();
+    let mean_rooms = per_run(rooms.iter().sum::<u32>() as f32);
+    println!();
+    println!(
+        "Rooms cleared: {mean_rooms:.2} on average, {} to {}",
+        rooms.iter().min().unwrap_or(&0),
+        rooms.iter().max().unwrap_or(&0)
+    );
+    println!(
+        "Deaths: {deaths} of {}, the others ran out of time",
+        runs.len()
+    );
+
+    let mut reached = BTreeMap::<u32, usize>::new();
+    for &room in &rooms {
+        *reached.entry(room).or_default() += 1;
+    }
+    println!("Runs by rooms cleared:");
+    for (room, runs) in reached {
+        println!("  {room:>3}: {runs}");
+    }
+
+    let mut damage_taken = BTreeMap::<&str, f32>::new();
+    let mut ability_uses = BTreeMap::<&str, u32>::new();
+    for run in runs {
+        for (source, damage) in &run.damage_taken {
+            *damage_taken.entry(source).or_default() += damage;
+        }
+        for (ability, uses) in &run.ability_uses {
+            *ability_uses.entry(ability).or_default() += uses;
+        }
+    }
+    println!("Damage taken per run:");
+    for (source, damage) in damage_taken {
+        println!("  {source:<16}{:>8.1}", per_run(damage));
+    }
+    println!("Ability uses per run:");
+    for (ability, uses) in ability_uses {
+        println!("  {ability:<16}{:>8.1}", per_run(uses as f32));
+    }
+
+    let overheats: u32 = runs.iter().map(|run| run.overheats).sum();
+    let overheating_runs = runs.iter().filter(|run| run.overheats > 0).count();
+    println!(
+        "Overheats: {:.2} per run, in {overheating_runs} of {} runs",
+        per_run(overheats as f32),
+        runs.len()
+    );
+
+    let rooms_played = runs.iter().map(|run| run.clear_times.len()).max();
+    println!("Seconds to clear each room:");
+    for room in 0..rooms_played.unwrap_or(0) {
+        let times: Vec<_> = runs
+            .iter()
+            .filter_map(|run| run.clear_times.get(room))
+            .collect();
+        let mean = times.iter().copied().sum::<f32>() / times.len() as f32;
+        println!(
+            "  {:>3}: {mean:>6.1} on average, cleared in {} runs",
+            room + 1,
+            times.len()
+        );
+    }
+}
+
+fn main() -> ExitCode {
+    if std::env::args().any(|arg| arg == "--help") {
+        print!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut harness = TestHarness::new();
+    harness
+        .app
+        .add_plugin(BotPlugin)
+        .insert_resource(options.bot.clone())
+        .insert_resource(options.heat.clone())
+        .insert_resource(options.difficulty.clone());
+
+    let mut runs = Vec::new();
+    for i in 0..options.runs {
+        let stats = simulate(&mut harness, &options, options.seed.wrapping_add(i));
+        println!(
+            "Run {i:>3} (seed {}): {} after clearing {} rooms in {:.1}s",
+            stats.seed,
+            if stats.died { "died" } else { "timed out" },
+            stats.rooms_cleared,
+            stats.time
+        );
+        runs.push(stats);
+    }
+    report(&runs);
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::{DualAxis, InputMap};
+use serde::{Deserialize, Serialize};
+
+use crate::player::PlayerActions;
+
+/// A single key or mouse button an action is bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                // Number keys are called Key1, Key2...
+                match name.strip_prefix("Key") {
+                    Some(digit) if !digit.is_empty() => digit.to_string(),
+                    _ => name,
+                }
+            }
+            Binding::Mouse(MouseButton::Left) => "Left click".to_string(),
+            Binding::Mouse(MouseButton::Right) => "Right click".to_string(),
+            Binding::Mouse(MouseButton::Middle) => "Middle click".to_string(),
+            Binding::Mouse(MouseButton::Other(button)) => format!("Mouse {button}"),
+        }
+    }
+}
+
+/// What each of the [`PlayerActions`] is bound to, stored in the settings file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bindings(pub Vec<(PlayerActions, Binding)>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self(vec![
+            (PlayerActions::Up, Binding::Key(KeyCode::W)),
+            (PlayerActions::Left, Binding::Key(KeyCode::A)),
+            (PlayerActions::Down, Binding::Key(KeyCode::S)),
+            (PlayerActions::Right, Binding::Key(KeyCode::D)),
+            (PlayerActions::Ability1, Binding::Key(KeyCode::Key1)),
+            (PlayerActions::Ability2, Binding::Key(KeyCode::Key2)),
+            (PlayerActions::Ability3, Binding::Key(KeyCode::Key3)),
+            (PlayerActions::Ability4, Binding::Key(KeyCode::Key4)),
+            (PlayerActions::UseAbility, Binding::Mouse(MouseButton::Left)),
+            (PlayerActions::Pause, Binding::Key(KeyCode::Escape)),
+            (PlayerActions::PreviousAbility, Binding::Key(KeyCode::Q)),
+            (PlayerActions::NextAbility, Binding::Key(KeyCode::E)),
+        ])
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: PlayerActions) -> Option<Binding> {
+        self.0
+            .iter()
+            .find(|(other, _)| *other == action)
+            .map(|(_, binding)| *binding)
+    }
+
+    pub fn set(&mut self, action: PlayerActions, binding: Binding) {
+        match self.0.iter_mut().find(|(other, _)| *other == action) {
+            Some((_, existing)) => *existing = binding,
+            None => self.0.push((action, binding)),
+        }
+    }
+
+    /// Actions that share their binding with another action.
+    pub fn conflicts(&self) -> Vec<PlayerActions> {
+        self.0
+            .iter()
+            .filter(|(action, binding)| {
+                self.0
+                    .iter()
+                    .any(|(other, other_binding)| other != action && other_binding == binding)
+            })
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    /// Binds actions missing from an older settings file to their defaults.
+    pub fn with_missing_defaults(mut self) -> Self {
+        for (action, binding) in Self::default().0 {
+            if self.get(action).is_none() {
+                self.0.push((action, binding));
+            }
+        }
+        self
+    }
+
+    /// Gamepad bindings can't be rebound, and are always added.
+    pub fn input_map(&self) -> InputMap<PlayerActions> {
+        let mut input_map = InputMap::default();
+        input_map
+            .insert(DualAxis::left_stick(), PlayerActions::Move)
+            .insert(DualAxis::right_stick(), PlayerActions::Aim)
+            .insert(
+                GamepadButtonType::LeftTrigger,
+                PlayerActions::PreviousAbility,
+            )
+            .insert(GamepadButtonType::RightTrigger, PlayerActions::NextAbility)
+            .insert(GamepadButtonType::RightTrigger2, PlayerActions::UseAbility)
+            .insert(GamepadButtonType::Start, PlayerActions::Pause);
+        for (action, binding) in &self.0 {
+            match *binding {
+                Binding::Key(key) => input_map.insert(key, *action),
+                Binding::Mouse(button) => input_map.insert(button, *action),
+            };
+        }
+        input_map
+    }
+}
+
+pub fn action_name(action: PlayerActions) -> &'static str {
+    match action {
+        PlayerActions::Left => "Move left",
+        PlayerActions::Right => "Move right",
+        PlayerActions::Up => "Move up",
+        PlayerActions::Down => "Move down",
+        PlayerActions::Ability1 => "Ability 1",
+        PlayerActions::Ability2 => "Ability 2",
+        PlayerActions::Ability3 => "Ability 3",
+        PlayerActions::Ability4 => "Ability 4",
+        PlayerActions::UseAbility => "Use ability",
+        PlayerActions::Pause => "Pause",
+        PlayerActions::PreviousAbility => "Previous ability",
+        PlayerActions::NextAbility => "Next ability",
+        PlayerActions::Move => "Move",
+        PlayerActions::Aim => "Aim",
+    }
+}
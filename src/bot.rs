@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    axislike::DualAxisData, buttonlike::ButtonState, prelude::ActionState,
+};
+
+use crate::{
+    abilities::{
+        cooldown::AbilityCooldown,
+        heat::{AddHeatOnUse, Heat, HeatConfig, Overheated},
+        take_damage::TakeDamage,
+        teleport::TeleportPower,
+        Loadout,
+    },
+    arena::Arena,
+    draft::DraftChoice,
+    enemy::Enemy,
+    health::Health,
+    mouse_position::MousePosition,
+    player::{Player, PlayerActions},
+    state::{GameState, OnExitRun},
+    timestep::{AddToStep, StepSet},
+};
+
+/// Plays in place of the player by driving its inputs, to simulate runs headless. Runs it plays
+/// are recorded like any other, so they can be watched as replays.
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bot>()
+            .init_resource::<BotInputs>()
+            .add_step_system(
+                play.run_if(in_state(GameState::Playing))
+                    .in_base_set(StepSet::PreUpdate),
+            )
+            .add_system(draft.in_schedule(OnEnter(GameState::Drafting)))
+            .add_system(reset.in_schedule(OnExitRun));
+    }
+}
+
+/// How the bot plays.
+#[derive(Resource, Clone, Debug)]
+pub struct Bot {
+    /// Enemies closer than this are run away from, and the bot closes in when all are further
+    pub kite_distance: f32,
+    /// Enemies further than this aren't attacked
+    pub attack_range: f32,
+    /// Teleports away when an enemy gets this close
+    pub panic_distance: f32,
+    /// Part of the overheat threshold the bot stays below
+    pub heat_margin: f32,
+    /// Abilities that hurt the player aren't used below this much health
+    pub min_health: f32,
+    /// Seconds the trigger is held, charging and channeling abilities that allow it
+    pub hold_time: f32,
+    /// Made in every draft
+    pub draft: DraftChoice,
+}
+
+impl Default for Bot {
+    fn default() -> Self {
+        Self {
+            kite_distance: 96.0,
+            attack_range: 160.0,
+            panic_distance: 32.0,
+            heat_margin: 0.8,
+            min_health: 40.0,
+            hold_time: 0.5,
+            draft: DraftChoice::Skip,
+        }
+    }
+}
+
+const ABILITY_ACTIONS: [PlayerActions; 4] = [
+    PlayerActions::Ability1,
+    PlayerActions::Ability2,
+    PlayerActions::Ability3,
+    PlayerActions::Ability4,
+];
+
+/// Keeps away from the walls by this much
+const WALL_MARGIN: f32 = 32.0;
+const TELEPORT_AIM_DISTANCE: f32 = 64.0;
+
+/// The buttons the bot pressed last step, and the ability it's using.
+#[derive(Resource, Clone, Default, Debug)]
+struct BotInputs {
+    pressed: Vec<PlayerActions>,
+    holding: Option<Holding>,
+    /// Abilities are tried in turns, starting from this slot
+    next_slot: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Holding {
+    elapsed: f32,
+    /// Fixed for abilities that aren't aimed at enemies
+    aim: Option<Vec2>,
+    hurts: bool,
+}
+
+fn play(
+    bot: Res<Bot>,
+    mut inputs: ResMut<BotInputs>,
+    mut players: Query<
+        (
+            &Transform,
+            &Loadout,
+            &Health,
+            &Heat,
+            Option<&Overheated>,
+            &mut ActionState<PlayerActions>,
+        ),
+        With<Player>,
+    >,
+    enemies: Query<&Transform, With<Enemy>>,
+    arenas: Query<&Arena>,
+    abilities: Query<(
+        Option<&AbilityCooldown>,
+        Option<&AddHeatOnUse>,
+        Option<&TeleportPower>,
+        Option<&Children>,
+    )>,
+    self_damage: Query<(), With<TakeDamage>>,
+    heat_config: Res<HeatConfig>,
+    mut mouse_position: ResMut<MousePosition>,
+    time: Res<Time>,
+) {
+    let Ok((transform, loadout, health, heat, overheated, mut action_state)) =
+        players.get_single_mut()
+    else {
+        return;
+    };
+    let position = transform.translation.truncate();
+    let enemies: Vec<_> = enemies
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .collect();
+    let nearest = enemies
+        .iter()
+        .copied()
+        .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)));
+    let heat_limit = heat_config.overheat_threshold * bot.heat_margin;
+    let was_using = inputs.pressed.contains(&PlayerActions::UseAbility);
+
+    let mut pressed = Vec::new();
+    if let Some(holding) = &mut inputs.holding {
+        holding.elapsed += time.delta_seconds();
+        let too_hurt = holding.hurts && **health < bot.min_health;
+        if holding.elapsed < bot.hold_time
+            && **heat < heat_limit
+            && overheated.is_none()
+            && !too_hurt
+        {
+            pressed.push(PlayerActions::UseAbility);
+        } else {
+            inputs.holding = None;
+        }
+    } else if !was_using && overheated.is_none() {
+        // Release the trigger for a step in between, so the next use is a new press
+        let count = loadout.abilities.len().min(ABILITY_ACTIONS.len());
+        let chosen = (0..count)
+            .map(|offset| (inputs.next_slot + offset) % count)
+            .find_map(|slot| {
+                let (cooldown, heat_on_use, teleport, side_effects) =
+                    abilities.get(*loadout.abilities.get(slot)?).ok()?;
+                let added_heat = heat_on_use.map_or(0.0, |heat| **heat);
+                let hurts = side_effects
+                    .is_some_and(|side_effects| self_damage.iter_many(side_effects).count() > 0);
+                if cooldown.is_some()
+                    || **heat + added_heat > heat_limit
+                    || (hurts && **health < bot.min_health)
+                {
+                    return None;
+                }
+                let nearest = nearest?;
+                let distance = nearest.distance(position);
+                if teleport.is_some() {
+                    // Only to get out of trouble, away from the closest enemy
+                    let away = (position - nearest).normalize_or_zero();
+                    let aim = position + away * TELEPORT_AIM_DISTANCE;
+                    (distance < bot.panic_distance).then_some((slot, Some(aim), hurts))
+                } else {
+                    (distance < bot.attack_range).then_some((slot, None, hurts))
+                }
+            });
+
+        if let Some((slot, aim, hurts)) = chosen {
+            pressed.extend([ABILITY_ACTIONS[slot], PlayerActions::UseAbility]);
+            inputs.holding = Some(Holding {
+                elapsed: 0.0,
+                aim,
+                hurts,
+            });
+            inputs.next_slot = (slot + 1) % count;
+        }
+    }
+
+    let aim = inputs
+        .holding
+        .as_ref()
+        .and_then(|holding| holding.aim)
+        .or(nearest);
+    if let Some(aim) = aim {
+        **mouse_position = aim;
+    }
+
+    for action in ABILITY_ACTIONS
+        .into_iter()
+        .chain([PlayerActions::UseAbility])
+    {
+        let now = pressed.contains(&action);
+        let before = inputs.pressed.contains(&action);
+        action_state.action_data_mut(action).state = match (now, before) {
+            (true, false) => ButtonState::JustPressed,
+            (true, true) => ButtonState::Pressed,
+            (false, true) => ButtonState::JustReleased,
+            (false, false) => ButtonState::Released,
+        };
+    }
+    let floor = arenas.get_single().ok().map(|arena| arena.floor);
+    let movement = kite(position, &enemies, nearest, floor, bot.kite_distance);
+    action_state.action_data_mut(PlayerActions::Move).axis_pair =
+        (movement != Vec2::ZERO).then(|| DualAxisData::from_xy(movement));
+
+    inputs.pressed = pressed;
+}
+
+/// Direction to move in to keep enemies at a distance, circling around them instead of backing
+/// into the outer walls. Walls inside the arena are left to sliding along them.
+fn kite(
+    position: Vec2,
+    enemies: &[Vec2],
+    nearest: Option<Vec2>,
+    floor: Option<Rect>,
+    distance: f32,
+) -> Vec2 {
+    let mut direction = Vec2::ZERO;
+    for &enemy in enemies {
+        let offset = position - enemy;
+        let closeness = 1.0 - offset.length() / distance;
+        if closeness > 0.0 {
+            let away = offset.normalize_or_zero();
+            direction += (away + away.perp() * 0.5) * closeness;
+        }
+    }
+
+    // Close in to stay in range once everything is far away
+    if let Some(nearest) = nearest {
+        if direction == Vec2::ZERO && nearest.distance(position) > distance * 1.5 {
+            direction = (nearest - position).normalize_or_zero() * 0.5;
+        }
+    }
+
+    if let Some(floor) = floor {
+        let offset = position - floor.center();
+        let outside = offset.abs() - (floor.half_size() - WALL_MARGIN);
+        if outside.x > 0.0 {
+            direction.x -= offset.x.signum() * outside.x / WALL_MARGIN;
+        }
+        if outside.y > 0.0 {
+            direction.y -= offset.y.signum() * outside.y / WALL_MARGIN;
+        }
+    }
+
+    direction.clamp_length_max(1.0)
+}
+
+fn draft(bot: Res<Bot>, mut choices: EventWriter<DraftChoice>) {
+    choices.send(bot.draft);
+}
+
+fn reset(mut inputs: ResMut<BotInputs>) {
+    *inputs = BotInputs::default();
+}
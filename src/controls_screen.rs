@@ -0,0 +1,320 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+use leafwing_input_manager::Actionlike;
+
+use crate::{
+    assets::GameAssets,
+    bindings::{action_name, Binding, Bindings},
+    player::PlayerActions,
+    settings::Settings,
+    settings_screen::SettingsScreenState,
+};
+
+pub struct ControlsScreenPlugin;
+
+impl Plugin for ControlsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_system(spawn_screen.in_schedule(OnEnter(SettingsScreenState::Controls)))
+            .add_systems(
+                (rebind, reset_bindings, go_back, show_bindings, highlight)
+                    .chain()
+                    .in_set(OnUpdate(SettingsScreenState::Controls)),
+            )
+            .add_system(despawn_screen.in_schedule(OnExit(SettingsScreenState::Controls)));
+    }
+}
+
+/// The action waiting for a key or mouse button to be pressed.
+#[derive(Resource, Clone, Default, Debug, Deref, DerefMut)]
+struct Rebinding(Option<PlayerActions>);
+
+#[derive(Component, Default, Debug)]
+struct ControlsScreenRoot;
+
+#[derive(Component, Clone, Copy, Debug)]
+struct RebindButton(PlayerActions);
+
+#[derive(Component, Clone, Copy, Debug)]
+struct BindingText(PlayerActions);
+
+#[derive(Component, Default, Debug)]
+struct ControlsStatusText;
+
+#[derive(Component, Default, Debug)]
+struct ResetBindingsButton;
+
+#[derive(Component, Default, Debug)]
+struct ControlsBackButton;
+
+const CONFLICT_COLOR: Color = Color::RED;
+const REBINDING_COLOR: Color = Color::YELLOW;
+
+fn spawn_screen(mut commands: Commands, assets: Res<GameAssets>, mut rebinding: ResMut<Rebinding>) {
+    // Also marks it as changed, so show_bindings fills in the texts
+    rebinding.0 = None;
+
+    let text_style = |font_size: f32| TextStyle {
+        font: assets.font_normal.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            ControlsScreenRoot,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    gap: Size::new(Val::Auto, Val::Px(8.0)),
+                    ..Default::default()
+                },
+                background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(2),
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Controls",
+                TextStyle {
+                    font: assets.font_italic.clone(),
+                    font_size: 64.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            for action in PlayerActions::variants().filter(|action| !action.is_axis()) {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            gap: Size::new(Val::Px(16.0), Val::Auto),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(action_name(action), text_style(32.0)),
+                            style: Style {
+                                size: Size::new(Val::Px(260.0), Val::Auto),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        });
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        align_items: AlignItems::Center,
+                                        justify_content: JustifyContent::Center,
+                                        size: Size::new(Val::Px(240.0), Val::Px(40.0)),
+                                        ..Default::default()
+                                    },
+                                    background_color: Color::DARK_GRAY.into(),
+                                    ..Default::default()
+                                },
+                                RebindButton(action),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    BindingText(action),
+                                    TextBundle::from_section("", text_style(32.0)),
+                                ));
+                            });
+                    });
+            }
+
+            parent.spawn((
+                ControlsStatusText,
+                TextBundle::from_section("", text_style(28.0)),
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        gap: Size::new(Val::Px(20.0), Val::Auto),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    let style = text_style(40.0);
+                    spawn_button(
+                        parent,
+                        ResetBindingsButton,
+                        "Reset to defaults",
+                        300.0,
+                        &style,
+                    );
+                    spawn_button(parent, ControlsBackButton, "Back", 200.0, &style);
+                });
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    marker: impl Component,
+    label: &str,
+    width: f32,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    size: Size::new(Val::Px(width), Val::Px(50.0)),
+                    ..Default::default()
+                },
+                background_color: Color::DARK_GRAY.into(),
+                ..Default::default()
+            },
+            marker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+fn despawn_screen(mut commands: Commands, screens: Query<Entity, With<ControlsScreenRoot>>) {
+    for e in &screens {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+/// Binds the action waiting for input to the next key or mouse button pressed, Escape cancels.
+fn rebind(
+    buttons: Query<(&RebindButton, &Interaction), Changed<Interaction>>,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<Settings>,
+) {
+    if let Some(action) = rebinding.0 {
+        if keys.just_pressed(KeyCode::Escape) {
+            rebinding.0 = None;
+            return;
+        }
+        let key = keys.get_just_pressed().next().map(|key| Binding::Key(*key));
+        let button = mouse
+            .get_just_pressed()
+            .next()
+            .map(|button| Binding::Mouse(*button));
+        let Some(binding) = key.or(button) else { return; };
+
+        if settings.bindings.get(action) != Some(binding) {
+            settings.bindings.set(action, binding);
+        }
+        rebinding.0 = None;
+        // Don't let the click that was just bound also start rebinding another action
+        return;
+    }
+
+    for (button, interaction) in &buttons {
+        if let Interaction::Clicked = interaction {
+            rebinding.0 = Some(button.0);
+        }
+    }
+}
+
+fn reset_bindings(
+    buttons: Query<&Interaction, (With<ResetBindingsButton>, Changed<Interaction>)>,
+    rebinding: Res<Rebinding>,
+    mut settings: ResMut<Settings>,
+) {
+    if rebinding.0.is_some() {
+        return;
+    }
+    for interaction in &buttons {
+        if let Interaction::Clicked = interaction {
+            settings.bindings = Bindings::default();
+        }
+    }
+}
+
+fn go_back(
+    buttons: Query<&Interaction, (With<ControlsBackButton>, Changed<Interaction>)>,
+    keys: Res<Input<KeyCode>>,
+    rebinding: Res<Rebinding>,
+    mut next_state: ResMut<NextState<SettingsScreenState>>,
+) {
+    // Escape cancels rebinding instead, which may have happened earlier this frame
+    if rebinding.0.is_some() || rebinding.is_changed() {
+        return;
+    }
+    let clicked = buttons
+        .iter()
+        .any(|interaction| matches!(interaction, Interaction::Clicked));
+    if clicked || keys.just_pressed(KeyCode::Escape) {
+        next_state.set(SettingsScreenState::Open);
+    }
+}
+
+fn show_bindings(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    mut binding_texts: Query<(&BindingText, &mut Text), Without<ControlsStatusText>>,
+    mut status_texts: Query<&mut Text, With<ControlsStatusText>>,
+) {
+    if !settings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+    let conflicts = settings.bindings.conflicts();
+
+    for (binding_text, mut text) in &mut binding_texts {
+        let action = binding_text.0;
+        let section = &mut text.sections[0];
+        if rebinding.0 == Some(action) {
+            section.value = "...".to_string();
+            section.style.color = REBINDING_COLOR;
+            continue;
+        }
+        section.value = settings
+            .bindings
+            .get(action)
+            .map_or_else(|| "Unbound".to_string(), |binding| binding.name());
+        section.style.color = if conflicts.contains(&action) {
+            CONFLICT_COLOR
+        } else {
+            Color::WHITE
+        };
+    }
+
+    let status = if let Some(action) = rebinding.0 {
+        format!(
+            "Press a key or mouse button for {}, Escape to cancel",
+            action_name(action)
+        )
+    } else if !conflicts.is_empty() {
+        "Some inputs are bound to more than one action".to_string()
+    } else {
+        String::new()
+    };
+    for mut text in &mut status_texts {
+        text.sections[0].value = status.clone();
+        text.sections[0].style.color = if rebinding.0.is_some() {
+            REBINDING_COLOR
+        } else {
+            CONFLICT_COLOR
+        };
+    }
+}
+
+fn highlight(
+    mut buttons: Query<(&mut BackgroundColor, &Interaction), (With<Button>, Changed<Interaction>)>,
+) {
+    for (mut color, interaction) in &mut buttons {
+        if let Interaction::Hovered = *interaction {
+            *color = BackgroundColor(Color::GRAY);
+        } else {
+            *color = BackgroundColor(Color::DARK_GRAY);
+        }
+    }
+}
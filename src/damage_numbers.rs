@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    assets::GameAssets,
+    health::{DamageDealtEvent, DamageType, DeathEvent, MaxHealth},
+    lifetime::Lifetime,
+    settings::Settings,
+    state::GameState,
+    timestep::FrameEvents,
+};
+
+pub struct DamageNumbersPlugin;
+
+impl Plugin for DamageNumbersPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<DamageNumber>()
+            // The damage was dealt by the steps earlier in the frame
+            .add_system(spawn_numbers)
+            // Their lifetime is game time, which stops while paused
+            .add_system(float_numbers.run_if(not(in_state(GameState::Paused))));
+    }
+}
+
+/// Hits on the same target within this many seconds of its number appearing are added to it.
+const AGGREGATE_WINDOW: f32 = 0.3;
+const NUMBER_LIFETIME: f32 = 0.8;
+/// Pixels per second
+const FLOAT_SPEED: f32 = 24.0;
+/// Text is rendered larger and scaled down so it stays sharp with the zoomed in camera
+const TEXT_SCALE: f32 = 0.25;
+const FONT_SIZE: f32 = 32.0;
+/// Hits dealing at least this fraction of the target's max health are shown as crits
+const CRIT_FRACTION: f32 = 0.5;
+
+#[derive(Component, Clone, Debug, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct DamageNumber {
+    pub target: Entity,
+    pub damage: f32,
+    pub damage_type: DamageType,
+    /// Seconds since it appeared
+    pub age: f32,
+}
+
+impl Default for DamageNumber {
+    fn default() -> Self {
+        Self {
+            target: Entity::PLACEHOLDER,
+            damage: 0.0,
+            damage_type: DamageType::default(),
+            age: 0.0,
+        }
+    }
+}
+
+fn damage_color(damage_type: DamageType) -> Color {
+    match damage_type {
+        DamageType::Explosive => Color::ORANGE,
+        DamageType::Ballistic => Color::WHITE,
+        DamageType::Melee => Color::RED,
+        DamageType::SelfInflicted => Color::PURPLE,
+        DamageType::Cold => Color::CYAN,
+        DamageType::Fire => Color::ORANGE_RED,
+    }
+}
+
+fn spawn_numbers(
+    mut commands: Commands,
+    dealt_events: Res<FrameEvents<DamageDealtEvent>>,
+    death_events: Res<FrameEvents<DeathEvent>>,
+    targets: Query<(&Transform, Option<&MaxHealth>)>,
+    mut numbers: Query<(&mut DamageNumber, &mut Text, &mut Lifetime)>,
+    assets: Res<GameAssets>,
+    settings: Res<Settings>,
+) {
+    if !settings.damage_numbers {
+        return;
+    }
+    let deaths: Vec<_> = death_events.iter().map(|death| death.died_id).collect();
+
+    for dealt in dealt_events.iter() {
+        let Ok((target_transform, max_health)) = targets.get(dealt.target) else { continue; };
+        let killed = deaths.contains(&dealt.target);
+        let crit =
+            max_health.is_some_and(|max_health| dealt.damage >= **max_health * CRIT_FRACTION);
+
+        let existing = numbers.iter_mut().find(|(number, ..)| {
+            number.target == dealt.target
+                && number.damage_type == dealt.damage_type
+                && number.age < AGGREGATE_WINDOW
+        });
+        if let Some((mut number, mut text, mut lifetime)) = existing {
+            number.damage += dealt.damage;
+            text.sections[0].value = format_damage(number.damage, killed);
+            if killed || crit {
+                text.sections[0].style.font_size = FONT_SIZE * 1.5;
+            }
+            lifetime.reset();
+            continue;
+        }
+
+        let font_size = if killed || crit {
+            FONT_SIZE * 1.5
+        } else {
+            FONT_SIZE
+        };
+        let position = target_transform.translation.truncate() + Vec2::new(0.0, 12.0);
+
+        commands.spawn((
+            DamageNumber {
+                target: dealt.target,
+                damage: dealt.damage,
+                damage_type: dealt.damage_type,
+                age: 0.0,
+            },
+            Text2dBundle {
+                text: Text::from_section(
+                    format_damage(dealt.damage, killed),
+                    TextStyle {
+                        font: assets.font_normal.clone(),
+                        font_size,
+                        color: damage_color(dealt.damage_type),
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(position.extend(950.0))
+                    .with_scale(Vec3::splat(TEXT_SCALE)),
+                ..Default::default()
+            },
+            Lifetime::new(Duration::from_secs_f32(NUMBER_LIFETIME)),
+        ));
+    }
+}
+
+fn format_damage(damage: f32, killed: bool) -> String {
+    // Damage over time deals fractions every frame, so never show a hit as 0
+    let damage = damage.ceil();
+    if killed {
+        format!("{damage}!")
+    } else {
+        format!("{damage}")
+    }
+}
+
+fn float_numbers(
+    mut numbers: Query<(&mut DamageNumber, &mut Transform, &mut Text, &Lifetime)>,
+    time: Res<Time>,
+) {
+    for (mut number, mut transform, mut text, lifetime) in &mut numbers {
+        number.age += time.delta_seconds();
+        transform.translation.y += FLOAT_SPEED * time.delta_seconds();
+
+        let alpha = 1.0 - lifetime.percent();
+        for section in &mut text.sections {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::{f32::consts::FRAC_PI_2, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{
+    enemy::{Enemy, EnemySet},
+    explosion::{ExplosionEvent, HandleExplosionSet},
+    health::{DamageType, DeathEvent},
+    lifetime::Lifetime,
+    player::Player,
+    projectile::ProjectileSet,
+    state::{OnEnterRun, OnExitRun},
+    timestep::AddToStep,
+};
+
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ExplodeOnDeath>()
+            .register_type::<LeaveCorpse>()
+            .register_type::<Corpse>()
+            .init_resource::<CauseOfDeath>()
+            .configure_step_set(
+                DeathEffectSet
+                    .after(ProjectileSet)
+                    .before(HandleExplosionSet)
+                    .before(EnemySet::Die),
+            )
+            .add_step_systems(
+                (explode_on_death, leave_corpses, record_cause_of_death).in_set(DeathEffectSet),
+            )
+            .add_system(reset_cause_of_death.in_schedule(OnEnterRun))
+            .add_system(despawn_corpses.in_schedule(OnExitRun));
+    }
+}
+
+/// Reacts to [`DeathEvent`]s while the dead entity still exists, before enemies are despawned.
+#[derive(SystemSet, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct DeathEffectSet;
+
+const CORPSE_LIFETIME: f32 = 5.0;
+
+/// Sends an explosion where the entity died.
+#[derive(Component, Clone, Default, Debug, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct ExplodeOnDeath {
+    pub range: f32,
+    pub force: f32,
+    pub damage: f32,
+}
+
+/// Leaves a faded copy of the entity's sprite behind when it dies.
+#[derive(Component, Clone, Default, Debug, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct LeaveCorpse;
+
+#[derive(Component, Clone, Default, Debug, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct Corpse;
+
+/// What killed the player in the current run, shown on the death screen.
+#[derive(Resource, Clone, Default, Debug, Deref, DerefMut)]
+pub struct CauseOfDeath(pub Option<String>);
+
+fn explode_on_death(
+    mut death_events: EventReader<DeathEvent>,
+    exploding: Query<(&ExplodeOnDeath, &Transform)>,
+    mut explosion_events: EventWriter<ExplosionEvent>,
+) {
+    for death in death_events.iter() {
+        let Ok((explosion, transform)) = exploding.get(death.died_id) else { continue; };
+
+        explosion_events.send(ExplosionEvent {
+            position: transform.translation.truncate(),
+            range: explosion.range,
+            force: explosion.force,
+            damage: explosion.damage,
+            effects: Vec::new(),
+            // Credit the chain reaction to whoever set it off
+            source: death.killer,
+        });
+    }
+}
+
+fn leave_corpses(
+    mut commands: Commands,
+    mut death_events: EventReader<DeathEvent>,
+    dying: Query<(&Sprite, &Handle<Image>, &Transform), With<LeaveCorpse>>,
+) {
+    for death in death_events.iter() {
+        let Ok((sprite, texture, transform)) = dying.get(death.died_id) else { continue; };
+
+        commands.spawn((
+            Corpse,
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::DARK_GRAY.with_a(0.6),
+                    ..sprite.clone()
+                },
+                texture: texture.clone(),
+                // Lying on its side, below everything that's still alive
+                transform: Transform {
+                    translation: transform.translation.truncate().extend(0.4),
+                    rotation: transform.rotation * Quat::from_rotation_z(FRAC_PI_2),
+                    ..*transform
+                },
+                ..Default::default()
+            },
+            Lifetime::new(Duration::from_secs_f32(CORPSE_LIFETIME)),
+        ));
+    }
+}
+
+fn record_cause_of_death(
+    mut death_events: EventReader<DeathEvent>,
+    players: Query<(), With<Player>>,
+    enemies: Query<&Enemy>,
+    mut cause_of_death: ResMut<CauseOfDeath>,
+) {
+    for death in death_events.iter() {
+        if players.get(death.died_id).is_err() {
+            continue;
+        }
+
+        let killer = death.killer.and_then(|killer| {
+            if killer == death.died_id {
+                Some("yourself")
+            } else {
+                enemies.get(killer).ok().map(|enemy| match enemy {
+                    Enemy::Bomb => "a bomb",
+                    Enemy::Cat => "a cat",
+                })
+            }
+        });
+        // The killer may already be gone, so fall back to how the damage was dealt
+        let killer = killer.unwrap_or(match death.damage_type {
+            DamageType::Explosive => "an explosion",
+            DamageType::Ballistic => "a stray bullet",
+            DamageType::Melee => "a scratch",
+            DamageType::SelfInflicted => "yourself",
+            DamageType::Cold => "the cold",
+            DamageType::Fire => "burns",
+        });
+
+        cause_of_death.0 = Some(format!("Killed by {killer}"));
+    }
+}
+
+fn reset_cause_of_death(mut cause_of_death: ResMut<CauseOfDeath>) {
+    cause_of_death.0 = None;
+}
+
+fn despawn_corpses(mut commands: Commands, corpses: Query<Entity, With<Corpse>>) {
+    for e in &corpses {
+        commands.entity(e).despawn_recursive();
+    }
+}
@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use crate::{assets::GameAssets, state::GameState};
+use crate::{assets::GameAssets, death::CauseOfDeath, seed::RunSeed, state::GameState};
 
 pub struct DeathScreenPlugin;
 
@@ -20,19 +20,44 @@ struct DeathScreen;
 #[derive(Component, Default, Debug)]
 struct DeathScreenTimer(pub Timer);
 
-fn show_deathscreen(mut commands: Commands, assets: Res<GameAssets>) {
+fn show_deathscreen(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    cause_of_death: Res<CauseOfDeath>,
+    run_seed: Res<RunSeed>,
+) {
+    let mut sections = vec![TextSection::new(
+        "You have died!",
+        TextStyle {
+            font: assets.font_italic.clone(),
+            font_size: 120.0,
+            color: Color::WHITE,
+        },
+    )];
+    if let Some(cause) = &**cause_of_death {
+        sections.push(TextSection::new(
+            format!("\n{cause}"),
+            TextStyle {
+                font: assets.font_normal.clone(),
+                font_size: 48.0,
+                color: Color::WHITE,
+            },
+        ));
+    }
+    sections.push(TextSection::new(
+        format!("\nSeed: {}", *run_seed),
+        TextStyle {
+            font: assets.font_normal.clone(),
+            font_size: 32.0,
+            color: Color::GRAY,
+        },
+    ));
+
     commands.spawn((
         DeathScreen,
         DeathScreenTimer(Timer::new(Duration::from_secs(3), TimerMode::Once)),
         TextBundle {
-            text: Text::from_section(
-                "You have died!",
-                TextStyle {
-                    font: assets.font_italic.clone(),
-                    font_size: 120.0,
-                    color: Color::WHITE,
-                },
-            ),
+            text: Text::from_sections(sections).with_alignment(TextAlignment::Center),
             style: Style {
                 position_type: PositionType::Absolute,
                 margin: UiRect::all(Val::Auto),
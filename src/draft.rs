@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{ExternalForce, Velocity};
+use bevy_turborand::{DelegatedRng, RngComponent};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    abilities::{
+        definition::{AbilityDefinition, SideEffectsFrom},
+        stats::StatModifiers,
+        Loadout,
+    },
+    assets::GameAssets,
+    player::Player,
+    seed::DraftRng,
+    state::GameState,
+    timestep::AddToStep,
+};
+
+pub struct DraftPlugin;
+
+impl Plugin for DraftPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DraftOffers>()
+            .add_step_event::<DraftChoice>()
+            .add_systems((stop_player, roll_offers).in_schedule(OnEnter(GameState::Drafting)))
+            .add_step_system(apply_choice.in_set(OnUpdate(GameState::Drafting)));
+    }
+}
+
+const OFFER_COUNT: usize = 3;
+
+/// A power combined with the side effects of another ability, offered in place of one slot.
+#[derive(Clone, Debug)]
+pub struct DraftOffer {
+    pub power: Handle<AbilityDefinition>,
+    pub side_effects: Handle<AbilityDefinition>,
+}
+
+/// The offers of the current draft, rolled when it starts.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct DraftOffers(pub Vec<DraftOffer>);
+
+/// Ends the draft, sent by the draft screen or a replay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DraftChoice {
+    /// Replaces the ability in `slot` of the loadout with the offer at `offer` in [`DraftOffers`]
+    Take {
+        offer: usize,
+        slot: usize,
+    },
+    Skip,
+}
+
+fn stop_player(mut player: Query<(&mut ExternalForce, &mut Velocity), With<Player>>) {
+    for (mut force, mut velocity) in &mut player {
+        *force = ExternalForce::default();
+        *velocity = Velocity::zero();
+    }
+}
+
+fn roll_offers(
+    assets: Res<GameAssets>,
+    mut offers: ResMut<DraftOffers>,
+    mut rngs: Query<&mut RngComponent, With<DraftRng>>,
+) {
+    let Ok(mut rng) = rngs.get_single_mut() else { return; };
+    let pool = [
+        assets.teleport_ability.clone(),
+        assets.fireball_ability.clone(),
+        assets.ice_wall_ability.clone(),
+        assets.shotgun_ability.clone(),
+    ];
+
+    offers.0 = (0..OFFER_COUNT)
+        .map(|_| DraftOffer {
+            power: rng.sample(&pool).unwrap().clone(),
+            side_effects: rng.sample(&pool).unwrap().clone(),
+        })
+        .collect();
+}
+
+fn apply_choice(
+    mut commands: Commands,
+    mut choices: EventReader<DraftChoice>,
+    offers: Res<DraftOffers>,
+    mut loadout: Query<&mut Loadout, With<Player>>,
+    modifiers: Query<&StatModifiers>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for &choice in choices.iter() {
+        let (offer, slot) = match choice {
+            DraftChoice::Take { offer, slot } => (offer, slot),
+            DraftChoice::Skip => {
+                next_state.set(GameState::Playing);
+                continue;
+            }
+        };
+        let Some(offer) = offers.0.get(offer) else { continue; };
+        let Ok(mut loadout) = loadout.get_single_mut() else { continue; };
+        let Some(&old_ability) = loadout.abilities.get(slot) else { continue; };
+
+        // The upgrades collected for the slot are kept for the rest of the run
+        let old_modifiers = modifiers.get(old_ability).cloned().unwrap_or_default();
+        let ability = commands
+            .spawn((
+                offer.power.clone(),
+                SideEffectsFrom(offer.side_effects.clone()),
+                old_modifiers,
+            ))
+            .id();
+
+        loadout.abilities[slot] = ability;
+        commands.entity(old_ability).despawn_recursive();
+
+        next_state.set(GameState::Playing);
+    }
+}
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+
+use crate::{
+    abilities::{definition::AbilityDefinition, Loadout},
+    assets::GameAssets,
+    draft::{DraftChoice, DraftOffers},
+    player::Player,
+    state::GameState,
+};
+
+pub struct DraftUiPlugin;
+
+impl Plugin for DraftUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedOffer>()
+            .add_systems(
+                (
+                    spawn_draft.run_if(resource_changed::<DraftOffers>()),
+                    select_offer,
+                    swap_slot,
+                    skip_draft,
+                    highlight_buttons,
+                )
+                    .in_set(OnUpdate(GameState::Drafting)),
+            )
+            .add_system(despawn_draft.in_schedule(OnExit(GameState::Drafting)));
+    }
+}
+
+#[derive(Component, Default, Debug)]
+struct DraftRoot;
+
+/// Index of the offer in [`DraftOffers`].
+#[derive(Component, Clone, Debug)]
+struct DraftOfferButton(usize);
+
+#[derive(Component, Clone, Debug)]
+struct DraftSlot(usize);
+
+#[derive(Component, Default, Debug)]
+struct DraftSkip;
+
+#[derive(Resource, Default, Debug)]
+struct SelectedOffer(Option<Entity>);
+
+fn spawn_draft(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    asset_server: Res<AssetServer>,
+    definitions: Res<Assets<AbilityDefinition>>,
+    offers: Res<DraftOffers>,
+    loadout: Query<&Loadout, With<Player>>,
+    abilities: Query<&Handle<AbilityDefinition>>,
+    mut selected: ResMut<SelectedOffer>,
+) {
+    let text_style = |font_size| TextStyle {
+        font: assets.font_normal.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+    selected.0 = None;
+
+    commands
+        .spawn((
+            DraftRoot,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    gap: Size::new(Val::Auto, Val::Px(20.0)),
+                    ..Default::default()
+                },
+                background_color: Color::BLACK.with_a(0.7).into(),
+                z_index: ZIndex::Global(1),
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Choose a new ability",
+                TextStyle {
+                    font: assets.font_italic.clone(),
+                    font_size: 64.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        gap: Size::new(Val::Px(20.0), Val::Auto),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for (index, offer) in offers.0.iter().enumerate() {
+                        let Some(power) = definitions.get(&offer.power) else { continue; };
+                        let Some(side_effects) = definitions.get(&offer.side_effects) else { continue; };
+                        let side_effect_names = if side_effects.side_effects.is_empty() {
+                            "No side effect".to_owned()
+                        } else {
+                            side_effects
+                                .side_effects
+                                .iter()
+                                .map(|side_effect| side_effect.id.replace('_', " "))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        };
+
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        align_items: AlignItems::Center,
+                                        flex_direction: FlexDirection::Column,
+                                        padding: UiRect::all(Val::Px(10.0)),
+                                        size: Size::new(Val::Px(240.0), Val::Auto),
+                                        ..Default::default()
+                                    },
+                                    background_color: Color::DARK_GRAY.into(),
+                                    ..Default::default()
+                                },
+                                DraftOfferButton(index),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(ImageBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(64.0), Val::Px(64.0)),
+                                        ..Default::default()
+                                    },
+                                    image: UiImage::new(asset_server.load(power.icon.as_str())),
+                                    ..Default::default()
+                                });
+                                parent.spawn(TextBundle::from_section(
+                                    power.name.clone(),
+                                    text_style(32.0),
+                                ));
+                                parent.spawn(TextBundle::from_section(
+                                    side_effect_names,
+                                    text_style(20.0),
+                                ));
+                            });
+                    }
+                });
+
+            parent.spawn(TextBundle::from_section("Replace a slot", text_style(32.0)));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        gap: Size::new(Val::Px(8.0), Val::Auto),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    let Ok(loadout) = loadout.get_single() else { return; };
+                    for (slot, &ability) in loadout.abilities.iter().enumerate() {
+                        let icon = abilities
+                            .get(ability)
+                            .ok()
+                            .and_then(|handle| definitions.get(handle))
+                            .map(|definition| asset_server.load(definition.icon.as_str()))
+                            .unwrap_or_default();
+
+                        parent.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(64.0), Val::Px(64.0)),
+                                    ..Default::default()
+                                },
+                                image: UiImage::new(icon),
+                                ..Default::default()
+                            },
+                            DraftSlot(slot),
+                        ));
+                    }
+                });
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+                            ..Default::default()
+                        },
+                        background_color: Color::DARK_GRAY.into(),
+                        ..Default::default()
+                    },
+                    DraftSkip,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Skip", text_style(48.0)));
+                });
+        });
+}
+
+fn despawn_draft(mut commands: Commands, drafts: Query<Entity, With<DraftRoot>>) {
+    for e in &drafts {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn select_offer(
+    offers: Query<(Entity, &Interaction), (With<DraftOfferButton>, Changed<Interaction>)>,
+    mut selected: ResMut<SelectedOffer>,
+) {
+    for (offer_id, interaction) in &offers {
+        if let Interaction::Clicked = interaction {
+            selected.0 = Some(offer_id);
+        }
+    }
+}
+
+fn swap_slot(
+    slots: Query<(&DraftSlot, &Interaction), Changed<Interaction>>,
+    offers: Query<&DraftOfferButton>,
+    selected: Res<SelectedOffer>,
+    mut choices: EventWriter<DraftChoice>,
+) {
+    for (slot, interaction) in &slots {
+        if !matches!(interaction, Interaction::Clicked) {
+            continue;
+        }
+        let Some(Ok(offer)) = selected.0.map(|offer_id| offers.get(offer_id)) else { continue; };
+
+        choices.send(DraftChoice::Take {
+            offer: offer.0,
+            slot: slot.0,
+        });
+    }
+}
+
+fn skip_draft(
+    skips: Query<&Interaction, (With<DraftSkip>, Changed<Interaction>)>,
+    mut choices: EventWriter<DraftChoice>,
+) {
+    for interaction in &skips {
+        if let Interaction::Clicked = interaction {
+            choices.send(DraftChoice::Skip);
+        }
+    }
+}
+
+fn highlight_buttons(
+    mut buttons: Query<
+        (
+            Entity,
+            &mut BackgroundColor,
+            &Interaction,
+            Option<&DraftSlot>,
+        ),
+        With<Button>,
+    >,
+    selected: Res<SelectedOffer>,
+) {
+    for (button_id, mut color, interaction, slot) in &mut buttons {
+        let hovered = matches!(interaction, Interaction::Hovered);
+        *color = match slot {
+            // Slots are tinted rather than colored so their icons stay visible
+            Some(_) if hovered => Color::GRAY.into(),
+            Some(_) => Color::WHITE.into(),
+            None if hovered || selected.0 == Some(button_id) => Color::GRAY.into(),
+            None => Color::DARK_GRAY.into(),
+        };
+    }
+}
@@ -7,8 +7,11 @@ use bevy_rapier2d::prelude::{
 
 use crate::{
     assets::GameAssets,
+    death::ExplodeOnDeath,
     explosion::{ExplosionEvent, HandleExplosionSet},
     health::{Health, MaxHealth},
+    status::Incapacitated,
+    timestep::AddToStep,
 };
 
 use super::{Enemy, EnemySet, SpawnEnemyEvent, Target};
@@ -18,7 +21,7 @@ pub(super) struct BombPlugin;
 impl Plugin for BombPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Bomb>()
-            .add_systems(
+            .add_step_systems(
                 (
                     insert_explosion_timers,
                     increment_explosion_timer,
@@ -27,7 +30,7 @@ impl Plugin for BombPlugin {
                     .chain()
                     .in_set(EnemySet::Attack),
             )
-            .add_systems((
+            .add_step_systems((
                 spawn_bomb.in_set(EnemySet::SpawnEnemies),
                 follow_target.in_set(EnemySet::AI),
             ));
@@ -41,7 +44,7 @@ const CIRCLE_EXPLODE_DISTANCE: f32 = 32.0;
 #[reflect(Component, Default, Debug)]
 pub struct Bomb;
 
-fn spawn_bomb(
+pub(super) fn spawn_bomb(
     mut commands: Commands,
     mut spawn_enemy_events: EventReader<SpawnEnemyEvent>,
     assets: Res<GameAssets>,
@@ -72,6 +75,11 @@ fn spawn_bomb(
                 },
                 Health(100.0),
                 MaxHealth(100.0),
+                ExplodeOnDeath {
+                    range: 64.0,
+                    force: 150.0,
+                    damage: 25.0,
+                },
             ));
         }
     }
@@ -120,7 +128,10 @@ fn insert_explosion_timers(
     }
 }
 
-fn increment_explosion_timer(mut timers: Query<&mut CircleExplosionTimer>, time: Res<Time>) {
+fn increment_explosion_timer(
+    mut timers: Query<&mut CircleExplosionTimer, Without<Incapacitated>>,
+    time: Res<Time>,
+) {
     for mut timer in &mut timers {
         timer.tick(time.delta());
     }
@@ -141,6 +152,8 @@ fn explode(
             range: 100.0,
             force: 200.0,
             damage: 50.0,
+            effects: Vec::new(),
+            source: Some(circle_entity),
         });
         commands.entity(circle_entity).despawn_recursive();
     }
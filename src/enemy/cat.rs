@@ -7,17 +7,21 @@ use bevy_rapier2d::prelude::{
 
 use crate::{
     assets::GameAssets,
-    health::{DamageEvent, Health, MaxHealth},
+    death::LeaveCorpse,
+    health::{DamageEvent, DamageType, Health, MaxHealth},
+    status::Incapacitated,
+    timestep::AddToStep,
 };
 
-use super::{Enemy, EnemySet, SpawnEnemyEvent, Target};
+use super::{bomb::spawn_bomb, Enemy, EnemySet, SpawnEnemyEvent, Target};
 
 pub struct CatPlugin;
 
 impl Plugin for CatPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(spawn_cat.in_set(EnemySet::SpawnEnemies))
-            .add_systems(
+        // After the bombs, so the enemies of a room get the same entities in every run
+        app.add_step_system(spawn_cat.after(spawn_bomb).in_set(EnemySet::SpawnEnemies))
+            .add_step_systems(
                 (
                     cat_tracking,
                     tracking_to_jump_transition,
@@ -28,7 +32,7 @@ impl Plugin for CatPlugin {
                     .chain()
                     .in_set(EnemySet::AI),
             )
-            .add_system(rotate_sprite);
+            .add_step_system(rotate_sprite.after(EnemySet::AI));
     }
 }
 
@@ -52,7 +56,7 @@ fn spawn_cat(
     assets: Res<GameAssets>,
 ) {
     for SpawnEnemyEvent { enemy, translation } in spawn_enemy_events.iter() {
-        if *enemy == Enemy::Bomb {
+        if *enemy == Enemy::Cat {
             commands.spawn((
                 Enemy::Cat,
                 Cat,
@@ -78,6 +82,7 @@ fn spawn_cat(
                 },
                 Health(100.0),
                 MaxHealth(100.0),
+                LeaveCorpse,
             ));
         }
     }
@@ -93,7 +98,7 @@ pub struct TrackingTimer(pub Timer);
 
 fn cat_tracking(
     player: Query<&Transform, With<Target>>,
-    mut cats: Query<(&Transform, &mut ExternalForce, &CatState)>,
+    mut cats: Query<(&Transform, &mut ExternalForce, &CatState), Without<Incapacitated>>,
 ) {
     for (cat_transform, mut cat_force, cat_state) in &mut cats {
         if *cat_state != CatState::Tracking {
@@ -122,12 +127,15 @@ fn cat_tracking(
 fn tracking_to_jump_transition(
     mut commands: Commands,
     player: Query<&Transform, With<Target>>,
-    mut cats: Query<(
-        Entity,
-        &Transform,
-        &mut CatState,
-        Option<&mut TrackingTimer>,
-    )>,
+    mut cats: Query<
+        (
+            Entity,
+            &Transform,
+            &mut CatState,
+            Option<&mut TrackingTimer>,
+        ),
+        Without<Incapacitated>,
+    >,
     time: Res<Time>,
 ) {
     for (cat_id, cat_transform, mut cat_state, tracking_timer) in &mut cats {
@@ -209,7 +217,10 @@ pub struct AttackTimeout(pub Timer);
 fn cat_attacking(
     mut commands: Commands,
     player: Query<(Entity, &Transform), With<Target>>,
-    mut cats: Query<(Entity, &Transform, &mut CatState, &mut AttackTimeout)>,
+    mut cats: Query<
+        (Entity, &Transform, &mut CatState, &mut AttackTimeout),
+        Without<Incapacitated>,
+    >,
     mut damage_events: EventWriter<DamageEvent>,
     time: Res<Time>,
 ) {
@@ -232,6 +243,9 @@ fn cat_attacking(
             damage_events.send(DamageEvent {
                 damaged_id: player_id,
                 damage: ATTACK_DAMAGE,
+                source: Some(cat_id),
+                damage_type: DamageType::Melee,
+                hit: true,
             });
             *cat_state = CatState::JumpingFromTarget;
             commands.entity(cat_id).remove::<AttackTimeout>();
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::health::DeathEvent;
+use crate::{abilities::AbilitySet, health::DeathEvent, room::RoomSet, timestep::AddToStep};
 
 use self::{bomb::BombPlugin, cat::CatPlugin};
 
@@ -13,10 +13,16 @@ impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Enemy>()
             .register_type::<Target>()
-            .add_event::<SpawnEnemyEvent>()
+            .add_step_event::<SpawnEnemyEvent>()
+            .configure_step_set(EnemySet::SpawnEnemies.after(RoomSet::Spawn))
+            .configure_step_sets(
+                (EnemySet::AI, EnemySet::Attack, EnemySet::Die)
+                    .chain()
+                    .after(AbilitySet),
+            )
             .add_plugin(BombPlugin)
             .add_plugin(CatPlugin)
-            .add_system(on_enemy_death.in_set(EnemySet::Die));
+            .add_step_system(on_enemy_death.in_set(EnemySet::Die));
     }
 }
 
@@ -36,6 +42,8 @@ pub struct SpawnEnemyEvent {
     pub translation: Vec2,
 }
 
+/// Enemies act on where the player ended up after this step's abilities, and new enemies join
+/// after the room has been checked.
 #[derive(SystemSet, Clone, Debug, Hash, PartialEq, Eq)]
 pub enum EnemySet {
     SpawnEnemies,
@@ -7,11 +7,13 @@ use bevy_turborand::{DelegatedRng, GlobalRng};
 
 use crate::{
     assets::GameAssets,
-    health::{DamageEvent, Health},
+    health::{DamageEvent, DamageType, Health},
     init::MainCamera,
     lifetime::Lifetime,
     player::Player,
     shake::Shake,
+    status::{ApplyStatusEvent, StatusEffect, StatusEffects},
+    timestep::AddToStep,
 };
 
 pub struct ExplosionPlugin;
@@ -19,11 +21,12 @@ pub struct ExplosionPlugin;
 impl Plugin for ExplosionPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<ExplosionEvent>()
-            .add_event::<ExplosionEvent>()
-            .add_systems(
+            .add_step_event::<ExplosionEvent>()
+            .add_step_systems(
                 (
                     apply_explosion_forces,
                     apply_explostion_damage,
+                    apply_explosion_statuses,
                     play_audio,
                     add_shake,
                     spawn_particles,
@@ -43,6 +46,10 @@ pub struct ExplosionEvent {
     pub range: f32,
     pub force: f32,
     pub damage: f32,
+    /// Applied to everything in range
+    pub effects: Vec<StatusEffect>,
+    /// Credited with the damage and effects
+    pub source: Option<Entity>,
 }
 
 fn apply_explosion_forces(
@@ -87,11 +94,39 @@ fn apply_explostion_damage(
             damage_events.send(DamageEvent {
                 damage: explosion.damage * object_distance_normalized,
                 damaged_id: object_id,
+                source: explosion.source,
+                damage_type: DamageType::Explosive,
+                hit: true,
             });
         }
     }
 }
 
+fn apply_explosion_statuses(
+    mut explosion_events: EventReader<ExplosionEvent>,
+    mut status_events: EventWriter<ApplyStatusEvent>,
+    objects: Query<(Entity, &Transform), With<StatusEffects>>,
+) {
+    for explosion in explosion_events.iter() {
+        for (object_id, object_transform) in &objects {
+            let object_position = object_transform.translation.truncate();
+            if object_position.distance(explosion.position) > explosion.range {
+                continue;
+            }
+
+            for effect in &explosion.effects {
+                status_events.send(ApplyStatusEvent {
+                    target: object_id,
+                    effect: StatusEffect {
+                        source: explosion.source,
+                        ..effect.clone()
+                    },
+                });
+            }
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug, Reflect, FromReflect, Component)]
 struct ExplosionParticle {
     pub velocity: Vec2,
@@ -164,7 +199,9 @@ fn add_shake(
 
             let strength = explosion.force * player_distance_normalized / 16.0;
 
-            camera.single_mut().amount += strength;
+            for mut shake in &mut camera {
+                shake.amount += strength;
+            }
         }
     }
 }
@@ -0,0 +1,42 @@
+use bevy::{app::PluginGroupBuilder, input::InputPlugin, prelude::*};
+use bevy_kira_audio::Audio;
+
+use crate::{assets::StubAssetsPlugin, timestep::StepRate};
+
+/// What [`crate::LabratsCorePlugins`] needs on top of `MinimalPlugins` to run without a window,
+/// rendering or audio, like in tests and simulations.
+pub struct HeadlessPlugins;
+
+impl PluginGroup for HeadlessPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(TransformPlugin)
+            .add(HierarchyPlugin)
+            .add(InputPlugin)
+            .add(AssetPlugin::default())
+            .add(HeadlessPlugin)
+    }
+}
+
+/// Stands in for the assets and resources normally provided by `DefaultPlugins` and
+/// [`crate::LabratsClientPlugins`].
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(StubAssetsPlugin)
+            // Nothing has to keep up with real time
+            .insert_resource(StepRate::PerUpdate(1))
+            // Colliders and the cool zone use meshes, which are never rendered
+            .add_asset::<Mesh>()
+            .add_asset::<ColorMaterial>()
+            .insert_resource(Audio::default())
+            .add_system(discard_sounds.in_base_set(CoreSet::Last));
+    }
+}
+
+/// Nothing plays the sounds queued on the audio channel, so drop them instead of letting them
+/// pile up.
+fn discard_sounds(mut audio: ResMut<Audio>) {
+    *audio = Audio::default();
+}
@@ -1,4 +1,9 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+    status::{StatusEffects, StatusKind},
+    timestep::{AddToStep, StepSet},
+};
 
 pub struct HealthPlugin;
 
@@ -7,16 +12,33 @@ impl Plugin for HealthPlugin {
         app.register_type::<Health>()
             .register_type::<MaxHealth>()
             .register_type::<DamageEvent>()
-            .add_event::<DamageEvent>()
-            .add_event::<DeathEvent>()
-            .add_systems(
-                (handle_damage, handle_death)
+            .register_type::<DamageType>()
+            .register_type::<Resistance>()
+            .register_type::<Resistances>()
+            .register_type::<IFrames>()
+            .register_type::<Invulnerable>()
+            .register_type::<Regeneration>()
+            .register_type::<Shield>()
+            .register_type::<Dead>()
+            .add_step_event::<DamageEvent>()
+            .add_step_event::<HealEvent>()
+            .add_frame_step_event::<HitEvent>()
+            .add_frame_step_event::<DamageDealtEvent>()
+            .add_frame_step_event::<DeathEvent>()
+            .add_step_systems(
+                (regenerate, handle_damage, handle_heal)
                     .chain()
-                    .in_base_set(CoreSet::PostUpdate),
-            );
+                    .in_set(HandleDamageSet)
+                    .in_base_set(StepSet::PostUpdate),
+            )
+            .add_step_systems((tick_invulnerability, recharge_shields));
     }
 }
 
+/// Applies [`DamageEvent`]s and [`HealEvent`]s, and sends the events describing what happened.
+#[derive(SystemSet, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct HandleDamageSet;
+
 #[derive(Component, Clone, Default, Debug, Deref, DerefMut, Reflect, FromReflect)]
 #[reflect(Component, Default, Debug)]
 pub struct Health(pub f32);
@@ -25,33 +47,267 @@ pub struct Health(pub f32);
 #[reflect(Component, Default, Debug)]
 pub struct MaxHealth(pub f32);
 
+/// Health restored every second.
+#[derive(Component, Clone, Default, Debug, Deref, DerefMut, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct Regeneration(pub f32);
+
+/// Absorbs damage before [`Health`], and recharges once the entity hasn't taken damage for a
+/// while.
+#[derive(Component, Clone, Default, Debug, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct Shield {
+    pub amount: f32,
+    pub max: f32,
+    /// Shield restored every second while recharging
+    pub recharge_rate: f32,
+    /// Reset whenever damage is taken, recharging starts once it finishes
+    pub recharge_delay: Timer,
+}
+
+impl Shield {
+    pub fn new(max: f32, recharge_rate: f32, recharge_delay: f32) -> Self {
+        Self {
+            amount: max,
+            max,
+            recharge_rate,
+            recharge_delay: Timer::from_seconds(recharge_delay, TimerMode::Once),
+        }
+    }
+}
+
+/// Restores health, up to [`MaxHealth`].
+#[derive(Clone, Debug)]
+pub struct HealEvent {
+    pub healed_id: Entity,
+    pub amount: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect, FromReflect)]
+pub enum DamageType {
+    #[default]
+    Explosive,
+    Ballistic,
+    Melee,
+    /// Dealt by an entity to itself, like ability side effects
+    SelfInflicted,
+    Cold,
+    /// Dealt over time by burning
+    Fire,
+}
+
 #[derive(Component, Clone, Debug, Reflect, FromReflect)]
 #[reflect(Debug)]
 pub struct DamageEvent {
     pub damaged_id: Entity,
     pub damage: f32,
+    /// Whoever is responsible for the damage, if anyone
+    pub source: Option<Entity>,
+    pub damage_type: DamageType,
+    /// Whether this counts as a hit, which is blocked by and starts [`IFrames`] and sends a
+    /// [`HitEvent`]. Off for damage over time, which would otherwise only tick once per window.
+    pub hit: bool,
+}
+
+/// Sent for every hit that wasn't blocked by i-frames, with the damage actually dealt.
+#[derive(Clone, Debug)]
+pub struct HitEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub damage: f32,
+    pub damage_type: DamageType,
+}
+
+/// Sent for every [`DamageEvent`] that was applied, with the damage actually dealt.
+#[derive(Clone, Debug)]
+pub struct DamageDealtEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub damage: f32,
+    pub damage_type: DamageType,
+}
+
+/// Seconds an entity ignores hits for after being hit.
+#[derive(Component, Clone, Default, Debug, Deref, DerefMut, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct IFrames(pub f32);
+
+/// Inserted on an entity with [`IFrames`] after it gets hit.
+#[derive(Component, Clone, Default, Debug, Deref, DerefMut, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct Invulnerable(pub Timer);
+
+#[derive(Clone, Copy, Debug, Default, Reflect, FromReflect)]
+pub struct Resistance {
+    pub damage_type: DamageType,
+    /// Fraction of the damage that is ignored. Negative values are weaknesses, so `-0.5` takes
+    /// 50% more damage.
+    pub amount: f32,
 }
 
-fn handle_damage(mut healths: Query<&mut Health>, mut damage_events: EventReader<DamageEvent>) {
+#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct Resistances(pub Vec<Resistance>);
+
+impl Resistances {
+    /// Multiplier for incoming damage of this type.
+    pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+        self.0
+            .iter()
+            .filter(|resistance| resistance.damage_type == damage_type)
+            .map(|resistance| (1.0 - resistance.amount).max(0.0))
+            .product()
+    }
+}
+
+fn handle_damage(
+    mut commands: Commands,
+    mut healths: Query<
+        (
+            &mut Health,
+            Option<&Resistances>,
+            Option<&StatusEffects>,
+            Option<&IFrames>,
+            Option<&Invulnerable>,
+            Option<&mut Shield>,
+        ),
+        Without<Dead>,
+    >,
+    mut damage_events: EventReader<DamageEvent>,
+    mut hit_events: EventWriter<HitEvent>,
+    mut dealt_events: EventWriter<DamageDealtEvent>,
+    mut death_events: EventWriter<DeathEvent>,
+    mut hit_this_frame: Local<HashSet<Entity>>,
+) {
+    hit_this_frame.clear();
     for damage in damage_events.iter() {
-        let Ok(mut health) = healths.get_mut(damage.damaged_id) else { return; };
-        **health -= damage.damage;
+        let Ok(target) = healths.get_mut(damage.damaged_id) else { continue; };
+        let (mut health, resistances, effects, iframes, invulnerable, shield) = target;
+
+        if damage.hit {
+            // Invulnerable is only inserted at the end of the frame
+            if invulnerable.is_some() || hit_this_frame.contains(&damage.damaged_id) {
+                continue;
+            }
+            if let Some(iframes) = iframes {
+                let timer = Timer::from_seconds(**iframes, TimerMode::Once);
+                commands
+                    .entity(damage.damaged_id)
+                    .insert(Invulnerable(timer));
+                hit_this_frame.insert(damage.damaged_id);
+            }
+        }
+
+        let resistance = resistances.map_or(1.0, |resistances| {
+            resistances.multiplier(damage.damage_type)
+        });
+        let vulnerability = effects.map_or(0.0, |effects| effects.strength(StatusKind::Vulnerable));
+        let dealt = damage.damage * resistance * (1.0 + vulnerability);
+
+        let mut remaining = dealt;
+        if let Some(mut shield) = shield {
+            let absorbed = remaining.min(shield.amount);
+            shield.amount -= absorbed;
+            remaining -= absorbed;
+            shield.recharge_delay.reset();
+        }
+        let was_alive = **health > 0.0;
+        **health -= remaining;
+
+        dealt_events.send(DamageDealtEvent {
+            target: damage.damaged_id,
+            source: damage.source,
+            damage: dealt,
+            damage_type: damage.damage_type,
+        });
+
+        if damage.hit {
+            hit_events.send(HitEvent {
+                target: damage.damaged_id,
+                source: damage.source,
+                damage: dealt,
+                damage_type: damage.damage_type,
+            });
+        }
+
+        // Only the damage that brings health to zero counts, later damage this frame is ignored
+        if was_alive && **health <= 0.0 {
+            death_events.send(DeathEvent {
+                died_id: damage.damaged_id,
+                killer: damage.source,
+                damage_type: damage.damage_type,
+                overkill: -**health,
+            });
+            commands.entity(damage.damaged_id).insert(Dead);
+        }
     }
 }
 
-#[derive(Component, Clone, Debug, Reflect, FromReflect)]
-#[reflect(Debug)]
-pub struct DeathEvent {
-    pub died_id: Entity,
+fn handle_heal(
+    mut healths: Query<(&mut Health, &MaxHealth), Without<Dead>>,
+    mut heal_events: EventReader<HealEvent>,
+) {
+    for heal in heal_events.iter() {
+        let Ok((mut health, max_health)) = healths.get_mut(heal.healed_id) else { continue; };
+        // Damage this frame may have killed it before Dead was inserted
+        if **health <= 0.0 {
+            continue;
+        }
+        **health = (**health + heal.amount).min(**max_health);
+    }
 }
 
-fn handle_death(
-    mut death_events: EventWriter<DeathEvent>,
-    changed_healths: Query<(Entity, &Health), Changed<Health>>,
+fn regenerate(
+    regenerating: Query<(Entity, &Regeneration, &Health, &MaxHealth), Without<Dead>>,
+    mut heal_events: EventWriter<HealEvent>,
+    time: Res<Time>,
+) {
+    for (entity, regeneration, health, max_health) in &regenerating {
+        if **health >= **max_health {
+            continue;
+        }
+        heal_events.send(HealEvent {
+            healed_id: entity,
+            amount: **regeneration * time.delta_seconds(),
+        });
+    }
+}
+
+fn recharge_shields(mut shields: Query<&mut Shield>, time: Res<Time>) {
+    for mut shield in &mut shields {
+        if !shield.recharge_delay.tick(time.delta()).finished() || shield.amount >= shield.max {
+            continue;
+        }
+        shield.amount =
+            (shield.amount + shield.recharge_rate * time.delta_seconds()).min(shield.max);
+    }
+}
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    mut invulnerable: Query<(Entity, &mut Invulnerable)>,
+    time: Res<Time>,
 ) {
-    for (id, health) in &changed_healths {
-        if **health < 0.0 {
-            death_events.send(DeathEvent { died_id: id });
+    for (entity, mut timer) in &mut invulnerable {
+        if timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
         }
     }
 }
+
+/// Sent once when an entity's health reaches zero.
+#[derive(Component, Clone, Debug, Reflect, FromReflect)]
+#[reflect(Debug)]
+pub struct DeathEvent {
+    pub died_id: Entity,
+    /// Source of the killing blow. It may already be despawned, like a bomb that blew itself up.
+    pub killer: Option<Entity>,
+    pub damage_type: DamageType,
+    /// Damage dealt past zero health
+    pub overkill: f32,
+}
+
+/// Inserted once an entity dies. Dead entities can't take damage or heal.
+#[derive(Component, Clone, Default, Debug, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct Dead;
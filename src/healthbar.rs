@@ -1,13 +1,15 @@
 use bevy::{prelude::*, sprite::Anchor};
 
-use crate::health::{Health, MaxHealth};
+use crate::health::{Health, MaxHealth, Shield};
 
 pub struct HealthBarPlugin;
 
 impl Plugin for HealthBarPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<HealthBar>()
-            .add_systems((despawn_bar, spawn_bar, render_bar));
+            .register_type::<ShieldBar>()
+            .add_systems((despawn_bar, spawn_bar, render_bar))
+            .add_systems((despawn_shield_bar, spawn_shield_bar, render_shield_bar));
     }
 }
 
@@ -17,6 +19,15 @@ pub struct HealthBar {
     pub value: Entity,
 }
 
+/// Drawn right above the [`HealthBar`] of entities with a [`Shield`].
+#[derive(Component, Clone, Debug, Reflect, FromReflect)]
+#[reflect(Debug)]
+pub struct ShieldBar {
+    pub value: Entity,
+}
+
+const SHIELD_BAR_OFFSET: Vec2 = Vec2::new(0.0, 13.0);
+
 fn spawn_bar(mut commands: Commands, added_healths: Query<(Entity, &Transform), Added<Health>>) {
     for (value, transform) in &added_healths {
         let pos = transform.translation.truncate();
@@ -70,3 +81,64 @@ fn render_bar(
         bar_transform.scale.x = value;
     }
 }
+
+fn spawn_shield_bar(
+    mut commands: Commands,
+    added_shields: Query<(Entity, &Transform), Added<Shield>>,
+) {
+    for (value, transform) in &added_shields {
+        let pos = transform.translation.truncate();
+        commands.spawn((
+            ShieldBar { value },
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::CYAN,
+                    custom_size: Some(Vec2::new(16.0, 2.0)),
+                    anchor: Anchor::BottomCenter,
+                    ..Default::default()
+                },
+                transform: Transform::from_translation((pos + SHIELD_BAR_OFFSET).extend(900.0)),
+                ..Default::default()
+            },
+        ));
+    }
+}
+
+fn despawn_shield_bar(
+    mut commands: Commands,
+    bars: Query<(Entity, &ShieldBar)>,
+    shields: Query<(), With<Shield>>,
+) {
+    for (bar_entity, bar) in &bars {
+        if shields.get(bar.value).is_err() {
+            commands.entity(bar_entity).despawn_recursive();
+        }
+    }
+}
+
+fn render_shield_bar(
+    mut bars: Query<(&mut Transform, &ShieldBar)>,
+    shields: Query<
+        (&Transform, &Shield),
+        (
+            Without<ShieldBar>,
+            Or<(Changed<Shield>, Changed<Transform>)>,
+        ),
+    >,
+) {
+    for (mut bar_transform, bar) in &mut bars {
+        let Ok((shield_transform, shield)) = shields.get(bar.value) else { continue; };
+
+        let shield_pos = shield_transform.translation.truncate();
+
+        bar_transform.translation =
+            (shield_pos + SHIELD_BAR_OFFSET).extend(bar_transform.translation.z);
+        let value = if shield.max > 0.0 {
+            (shield.amount / shield.max).max(0.0)
+        } else {
+            0.0
+        };
+
+        bar_transform.scale.x = value;
+    }
+}
@@ -1,14 +1,18 @@
 use bevy::prelude::*;
 
-use crate::{abilities::heat::Heat, player::Player, state::GameState};
+use crate::{
+    abilities::heat::{Heat, HeatConfig},
+    player::Player,
+    state::{GameState, OnEnterRun, OnExitRun},
+};
 
 pub struct HeatUiPlugin;
 
 impl Plugin for HeatUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(spawn_heat_ui.in_schedule(OnEnter(GameState::Playing)))
+        app.add_system(spawn_heat_ui.in_schedule(OnEnterRun))
             .add_system(set_slider.in_set(OnUpdate(GameState::Playing)))
-            .add_system(despawn_heat_ui.in_schedule(OnExit(GameState::Playing)));
+            .add_system(despawn_heat_ui.in_schedule(OnExitRun));
     }
 }
 
@@ -70,10 +74,12 @@ fn despawn_heat_ui(mut commands: Commands, query: Query<Entity, With<HeatUi>>) {
 fn set_slider(
     mut sliders: Query<&mut Style, With<HeatUiSlider>>,
     player_heat: Query<&Heat, With<Player>>,
+    config: Res<HeatConfig>,
 ) {
     for mut style in &mut sliders {
         let Ok(heat) = player_heat.get_single() else { return; };
 
-        style.size.width = Val::Percent(heat.clamp(0.0, 100.0));
+        let percent = **heat / config.overheat_threshold * 100.0;
+        style.size.width = Val::Percent(percent.clamp(0.0, 100.0));
     }
 }
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use bevy_kira_audio::{Audio, AudioControl};
+use bevy_rapier2d::prelude::ExternalImpulse;
+
+use crate::{
+    assets::GameAssets,
+    health::{DamageType, HandleDamageSet, HitEvent},
+    init::MainCamera,
+    player::Player,
+    shake::Shake,
+    timestep::{AddToStep, StepSet},
+};
+
+pub struct HitPlugin;
+
+impl Plugin for HitPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<HitFlash>()
+            .add_step_systems(
+                (flash_sprites, knockback, add_shake, play_audio)
+                    .in_base_set(StepSet::PostUpdate)
+                    .after(HandleDamageSet),
+            )
+            .add_step_system(fade_flashes);
+    }
+}
+
+const FLASH_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+const FLASH_DURATION: f32 = 0.15;
+const KNOCKBACK_IMPULSE: f32 = 48.0;
+
+/// Tints a sprite after it gets hit, restoring its color once the timer finishes.
+#[derive(Component, Clone, Default, Debug, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct HitFlash {
+    pub timer: Timer,
+    pub color: Color,
+}
+
+fn flash_sprites(
+    mut commands: Commands,
+    mut hit_events: EventReader<HitEvent>,
+    mut sprites: Query<(&mut Sprite, Option<&mut HitFlash>)>,
+) {
+    for hit in hit_events.iter() {
+        let Ok((mut sprite, flash)) = sprites.get_mut(hit.target) else { continue; };
+
+        match flash {
+            // Keep the original color from the first hit
+            Some(mut flash) => flash.timer.reset(),
+            None => {
+                commands.entity(hit.target).insert(HitFlash {
+                    timer: Timer::from_seconds(FLASH_DURATION, TimerMode::Once),
+                    color: sprite.color,
+                });
+                sprite.color = FLASH_COLOR.with_a(sprite.color.a());
+            }
+        }
+    }
+}
+
+fn fade_flashes(
+    mut commands: Commands,
+    mut flashes: Query<(Entity, &mut Sprite, &mut HitFlash)>,
+    time: Res<Time>,
+) {
+    for (entity, mut sprite, mut flash) in &mut flashes {
+        if flash.timer.tick(time.delta()).finished() {
+            sprite.color = flash.color;
+            commands.entity(entity).remove::<HitFlash>();
+        }
+    }
+}
+
+/// Pushes targets away from whoever hit them. Explosions already push everything around.
+fn knockback(
+    mut hit_events: EventReader<HitEvent>,
+    mut targets: Query<(&mut ExternalImpulse, &Transform)>,
+    sources: Query<&Transform>,
+) {
+    for hit in hit_events.iter() {
+        if !matches!(hit.damage_type, DamageType::Melee | DamageType::Ballistic) {
+            continue;
+        }
+        let Some(Ok(source_transform)) = hit.source.map(|source| sources.get(source)) else { continue; };
+        let Ok((mut impulse, target_transform)) = targets.get_mut(hit.target) else { continue; };
+
+        let direction = (target_transform.translation - source_transform.translation)
+            .truncate()
+            .normalize_or_zero();
+        impulse.impulse += direction * KNOCKBACK_IMPULSE;
+    }
+}
+
+fn add_shake(
+    mut hit_events: EventReader<HitEvent>,
+    mut camera: Query<&mut Shake, With<MainCamera>>,
+    players: Query<(), With<Player>>,
+) {
+    for hit in hit_events.iter() {
+        if players.get(hit.target).is_err() {
+            continue;
+        }
+        for mut shake in &mut camera {
+            shake.amount += (hit.damage / 20.0).min(3.0);
+        }
+    }
+}
+
+fn play_audio(
+    mut hit_events: EventReader<HitEvent>,
+    players: Query<(), With<Player>>,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+) {
+    let player_hits = hit_events
+        .iter()
+        .filter(|hit| players.get(hit.target).is_ok());
+    if player_hits.count() > 0 {
+        audio.play(assets.hurt_sound.clone());
+    }
+}
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::GameAssets,
+    enemy::Enemy,
+    health::{Dead, Health, HitEvent, MaxHealth},
+    player::Player,
+    room_manager::{CurrentRoom, RunTime},
+    state::{GameState, OnEnterRun, OnExitRun},
+    timestep::FrameEvents,
+};
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_hud.in_schedule(OnEnterRun))
+            .add_systems(
+                (set_health, set_run_status, flash_on_hit, fade_flash)
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(despawn_hud.in_schedule(OnExitRun));
+    }
+}
+
+#[derive(Component, Default, Debug)]
+pub struct Hud;
+
+#[derive(Component, Default, Debug)]
+struct HudHealthBar;
+
+#[derive(Component, Default, Debug)]
+struct HudHealthFill;
+
+#[derive(Component, Default, Debug)]
+struct HudHealthText;
+
+#[derive(Component, Default, Debug)]
+struct HudRunStatus;
+
+#[derive(Component, Debug)]
+struct HudFlash(pub Timer);
+
+const HEALTH_BAR_WIDTH: f32 = 400.0;
+const BAR_COLOR: Color = Color::GRAY;
+const FLASH_COLOR: Color = Color::WHITE;
+const FLASH_DURATION: f32 = 0.2;
+
+fn spawn_hud(mut commands: Commands, assets: Res<GameAssets>) {
+    let text_style = TextStyle {
+        font: assets.font_normal.clone(),
+        font_size: 32.0,
+        color: Color::WHITE,
+    };
+
+    // Starts finished so it doesn't flash when the run starts
+    let mut flash = Timer::from_seconds(FLASH_DURATION, TimerMode::Once);
+    flash.tick(flash.duration());
+
+    commands
+        .spawn((
+            Hud,
+            HudHealthBar,
+            HudFlash(flash),
+            NodeBundle {
+                style: Style {
+                    position: UiRect::new(Val::Px(20.0), Val::Auto, Val::Auto, Val::Px(20.0)),
+                    position_type: PositionType::Absolute,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    size: Size::new(Val::Px(HEALTH_BAR_WIDTH), Val::Px(64.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                background_color: BAR_COLOR.into(),
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                HudHealthFill,
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect::new(
+                            Val::Px(10.0),
+                            Val::Auto,
+                            Val::Px(10.0),
+                            Val::Px(10.0),
+                        ),
+                        size: Size::new(Val::Px(HEALTH_BAR_WIDTH - 20.0), Val::Auto),
+                        ..Default::default()
+                    },
+                    background_color: Color::RED.into(),
+                    ..Default::default()
+                },
+            ));
+            parent.spawn((
+                HudHealthText,
+                TextBundle::from_section("", text_style.clone()),
+            ));
+        });
+
+    commands.spawn((
+        Hud,
+        HudRunStatus,
+        TextBundle {
+            text: Text::from_section("", text_style),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::new(Val::Auto, Val::Px(20.0), Val::Auto, Val::Px(20.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    ));
+}
+
+fn despawn_hud(mut commands: Commands, query: Query<Entity, With<Hud>>) {
+    for e in &query {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn set_health(
+    player: Query<(&Health, &MaxHealth), With<Player>>,
+    mut fills: Query<&mut Style, With<HudHealthFill>>,
+    mut texts: Query<&mut Text, With<HudHealthText>>,
+) {
+    let Ok((health, max_health)) = player.get_single() else { return; };
+    let percent = (**health / **max_health).clamp(0.0, 1.0);
+
+    for mut style in &mut fills {
+        style.size.width = Val::Px((HEALTH_BAR_WIDTH - 20.0) * percent);
+    }
+    for mut text in &mut texts {
+        text.sections[0].value = format!("{} / {}", health.max(0.0).ceil(), max_health.ceil());
+    }
+}
+
+fn set_run_status(
+    mut texts: Query<&mut Text, With<HudRunStatus>>,
+    enemies: Query<(), (With<Enemy>, Without<Dead>)>,
+    current_room: Res<CurrentRoom>,
+    run_time: Res<RunTime>,
+) {
+    let seconds = run_time.elapsed().as_secs();
+    let status = format!(
+        "Room {}  |  {} enemies left  |  {:02}:{:02}",
+        current_room.0 + 1,
+        enemies.iter().count(),
+        seconds / 60,
+        seconds % 60,
+    );
+
+    for mut text in &mut texts {
+        text.sections[0].value = status.clone();
+    }
+}
+
+fn flash_on_hit(
+    hit_events: Res<FrameEvents<HitEvent>>,
+    player: Query<(), With<Player>>,
+    mut bars: Query<(&mut HudFlash, &mut BackgroundColor)>,
+) {
+    let player_hits = hit_events
+        .iter()
+        .filter(|hit| player.get(hit.target).is_ok());
+    if player_hits.count() == 0 {
+        return;
+    }
+    for (mut flash, mut color) in &mut bars {
+        flash.0.reset();
+        *color = FLASH_COLOR.into();
+    }
+}
+
+fn fade_flash(mut bars: Query<(&mut HudFlash, &mut BackgroundColor)>, time: Res<Time>) {
+    for (mut flash, mut color) in &mut bars {
+        if flash.0.finished() {
+            continue;
+        }
+        let percent = flash.0.tick(time.delta()).percent();
+        let faded = Vec4::from(FLASH_COLOR).lerp(Vec4::from(BAR_COLOR), percent);
+        *color = Color::from(faded).into();
+    }
+}
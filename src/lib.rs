@@ -0,0 +1,147 @@
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use abilities::AbilitiesPlugin;
+use ability_ui::AbilityUiPlugin;
+use arena::ArenaPlugin;
+use assets::AssetsPlugin;
+use audio::AudioPlugin;
+use bevy::{app::PluginGroupBuilder, prelude::*};
+use bevy_ecs_tilemap::TilemapPlugin;
+use bevy_turborand::RngPlugin;
+use controls_screen::ControlsScreenPlugin;
+use damage_numbers::DamageNumbersPlugin;
+use death::DeathPlugin;
+use death_screen::DeathScreenPlugin;
+use draft::DraftPlugin;
+use draft_ui::DraftUiPlugin;
+use enemy::EnemyPlugin;
+use explosion::ExplosionPlugin;
+use health::HealthPlugin;
+use healthbar::HealthBarPlugin;
+use heat_ui::HeatUiPlugin;
+use hit::HitPlugin;
+use hud::HudPlugin;
+use init::InitPlugin;
+use lifetime::LifetimePlugin;
+use main_menu::MainMenuPlugin;
+use mouse_position::MousePositionPlugin;
+use pause::PausePlugin;
+use physics::PhysicsPlugin;
+use player::PlayerPlugin;
+use projectile::ProjectilePlugin;
+use replay::ReplayPlugin;
+use replay_viewer::ReplayViewerPlugin;
+use room::RoomPlugin;
+use room_manager::RoomManagerPlugin;
+use seed::SeedPlugin;
+use settings::SettingsPlugin;
+use settings_screen::SettingsScreenPlugin;
+use shake::ShakePlugin;
+use state::GlobalStatePlugin;
+use status::StatusPlugin;
+use timestep::TimestepPlugin;
+use upgrade::UpgradePlugin;
+
+pub mod abilities;
+pub mod ability_ui;
+pub mod arena;
+pub mod assets;
+pub mod audio;
+pub mod bindings;
+#[cfg(feature = "testing")]
+pub mod bot;
+pub mod controls_screen;
+pub mod damage_numbers;
+pub mod death;
+pub mod death_screen;
+pub mod draft;
+pub mod draft_ui;
+pub mod enemy;
+pub mod explosion;
+pub mod headless;
+pub mod health;
+pub mod healthbar;
+pub mod heat_ui;
+pub mod hit;
+pub mod hud;
+pub mod init;
+pub mod lifetime;
+pub mod main_menu;
+pub mod mouse_position;
+pub mod pause;
+pub mod physics;
+pub mod player;
+pub mod projectile;
+pub mod replay;
+pub mod replay_viewer;
+pub mod room;
+pub mod room_manager;
+pub mod seed;
+pub mod settings;
+pub mod settings_screen;
+pub mod shake;
+pub mod state;
+pub mod status;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod timestep;
+pub mod upgrade;
+
+/// The game logic, which runs without a window, rendering or audio. Add
+/// [`LabratsClientPlugins`] to play it, or [`headless::HeadlessPlugins`] to simulate it.
+pub struct LabratsCorePlugins;
+
+impl PluginGroup for LabratsCorePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(GlobalStatePlugin)
+            .add(SettingsPlugin)
+            .add(TimestepPlugin)
+            .add(RngPlugin::default())
+            .add(SeedPlugin)
+            .add(PhysicsPlugin)
+            .add(PlayerPlugin)
+            .add(ArenaPlugin)
+            .add(RoomPlugin)
+            .add(AbilitiesPlugin)
+            .add(HealthPlugin)
+            .add(HitPlugin)
+            .add(DeathPlugin)
+            .add(EnemyPlugin)
+            .add(ExplosionPlugin)
+            .add(ProjectilePlugin)
+            .add(StatusPlugin)
+            .add(MousePositionPlugin)
+            .add(LifetimePlugin)
+            .add(RoomManagerPlugin)
+            .add(UpgradePlugin)
+            .add(DraftPlugin)
+            .add(ReplayPlugin)
+    }
+}
+
+/// Assets, camera, UI, menus and audio, on top of [`LabratsCorePlugins`] and `DefaultPlugins`.
+pub struct LabratsClientPlugins;
+
+impl PluginGroup for LabratsClientPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(InitPlugin)
+            .add(AssetsPlugin)
+            .add(TilemapPlugin)
+            .add(DamageNumbersPlugin)
+            .add(HealthBarPlugin)
+            .add(DeathScreenPlugin)
+            .add(DraftUiPlugin)
+            .add(MainMenuPlugin)
+            .add(AbilityUiPlugin)
+            .add(ShakePlugin)
+            .add(HeatUiPlugin)
+            .add(HudPlugin)
+            .add(PausePlugin)
+            .add(SettingsScreenPlugin)
+            .add(ControlsScreenPlugin)
+            .add(AudioPlugin)
+            .add(ReplayViewerPlugin)
+    }
+}
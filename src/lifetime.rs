@@ -2,12 +2,14 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
+use crate::timestep::{AddToStep, StepSet};
+
 pub struct LifetimePlugin;
 
 impl Plugin for LifetimePlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Lifetime>()
-            .add_system(lifetime_system.in_base_set(CoreSet::PostUpdate));
+            .add_step_system(lifetime_system.in_base_set(StepSet::PostUpdate));
     }
 }
 
@@ -4,15 +4,28 @@ use leafwing_input_manager::{
     Actionlike, InputManagerBundle,
 };
 
-use crate::{assets::GameAssets, state::GameState};
+use crate::{
+    assets::GameAssets,
+    seed::{NextRunSeed, RunSeed},
+    settings_screen::SettingsScreenState,
+    state::GameState,
+};
 
 pub struct MainMenuPlugin;
 
 impl Plugin for MainMenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(InputManagerPlugin::<MainMenuActions>::default())
+            .init_resource::<MenuFocus>()
+            .init_resource::<SeedEntry>()
             .add_system(spawn_menu.in_schedule(OnEnter(GameState::MainMenu)))
-            .add_systems((handle_actions, highlight).in_set(OnUpdate(GameState::MainMenu)))
+            .add_systems(
+                (navigate_with_gamepad, handle_actions, enter_seed)
+                    .chain()
+                    .distributive_run_if(in_state(SettingsScreenState::Closed))
+                    .in_set(OnUpdate(GameState::MainMenu)),
+            )
+            .add_system(highlight.in_set(OnUpdate(GameState::MainMenu)))
             .add_system(despawn_menu.in_schedule(OnExit(GameState::MainMenu)));
     }
 }
@@ -20,13 +33,43 @@ impl Plugin for MainMenuPlugin {
 #[derive(Component, Default, Debug)]
 struct MainMenuRoot;
 
+#[derive(Component, Default, Debug)]
+struct SeedText;
+
 #[derive(Actionlike, Component, PartialEq, Eq, Clone, Copy, Hash, Debug)]
 enum MainMenuActions {
     Play,
+    Settings,
     Exit,
 }
 
-fn spawn_menu(mut commands: Commands, assets: Res<GameAssets>) {
+/// Buttons in the order they're navigated with a gamepad
+const MENU_ORDER: [MainMenuActions; 3] = [
+    MainMenuActions::Play,
+    MainMenuActions::Settings,
+    MainMenuActions::Exit,
+];
+/// How far the left stick has to be pushed to move to the next button
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// Index in [`MENU_ORDER`] of the button selected with a gamepad, none until a gamepad is used.
+#[derive(Resource, Clone, Default, Debug)]
+struct MenuFocus(Option<usize>);
+
+/// Hex digits typed for the seed of the next run.
+#[derive(Resource, Clone, Default, Debug)]
+struct SeedEntry(String);
+
+fn spawn_menu(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut focus: ResMut<MenuFocus>,
+    next_seed: Res<NextRunSeed>,
+    mut seed_entry: ResMut<SeedEntry>,
+) {
+    focus.0 = None;
+    seed_entry.0 = next_seed.0.map(|seed| seed.to_string()).unwrap_or_default();
+
     commands
         .spawn((
             MainMenuRoot,
@@ -78,6 +121,36 @@ fn spawn_menu(mut commands: Commands, assets: Res<GameAssets>) {
                         ..Default::default()
                     });
                 });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+                            ..Default::default()
+                        },
+                        background_color: Color::DARK_GRAY.into(),
+                        ..Default::default()
+                    },
+                    ActionStateDriver {
+                        action: MainMenuActions::Settings,
+                        entity: parent_id,
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section(
+                            "Settings",
+                            TextStyle {
+                                font: assets.font_normal.clone(),
+                                font_size: 48.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        ..Default::default()
+                    });
+                });
             parent
                 .spawn((
                     ButtonBundle {
@@ -108,24 +181,133 @@ fn spawn_menu(mut commands: Commands, assets: Res<GameAssets>) {
                         ..Default::default()
                     });
                 });
+            parent.spawn((
+                SeedText,
+                TextBundle::from_section(
+                    seed_label(&seed_entry.0),
+                    TextStyle {
+                        font: assets.font_normal.clone(),
+                        font_size: 24.0,
+                        color: Color::GRAY,
+                    },
+                ),
+            ));
         });
 }
 
+fn seed_label(digits: &str) -> String {
+    if digits.is_empty() {
+        "Seed: random (type a hex seed to replay a run)".to_string()
+    } else {
+        format!("Seed: {digits}")
+    }
+}
+
+/// Typing hex digits sets the seed of the next run, backspace removes them.
+fn enter_seed(
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut next_seed: ResMut<NextRunSeed>,
+    mut texts: Query<&mut Text, With<SeedText>>,
+    mut seed_entry: ResMut<SeedEntry>,
+) {
+    let digits = &mut seed_entry.0;
+    let before = digits.clone();
+    for character in characters.iter() {
+        if character.char.is_ascii_hexdigit() && digits.len() < RunSeed::MAX_DIGITS {
+            digits.push(character.char.to_ascii_uppercase());
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        digits.pop();
+    }
+    if *digits == before {
+        return;
+    }
+
+    next_seed.0 = RunSeed::parse(digits);
+    for mut text in &mut texts {
+        text.sections[0].value = seed_label(digits);
+    }
+}
+
 fn handle_actions(
     input: Query<&ActionState<MainMenuActions>>,
     mut exit_events: EventWriter<AppExit>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut next_settings_state: ResMut<NextState<SettingsScreenState>>,
 ) {
     for input in &input {
         if input.just_pressed(MainMenuActions::Play) {
             next_state.set(GameState::Playing);
         }
+        if input.just_pressed(MainMenuActions::Settings) {
+            next_settings_state.set(SettingsScreenState::Open);
+        }
         if input.just_pressed(MainMenuActions::Exit) {
             exit_events.send_default();
         }
     }
 }
 
+/// Moves the focus with the D-pad or left stick, and presses the focused button with South.
+fn navigate_with_gamepad(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut focus: ResMut<MenuFocus>,
+    mut menus: Query<&mut ActionState<MainMenuActions>>,
+    mut stick_held: Local<bool>,
+) {
+    let mut step = 0;
+    let mut activate = false;
+    let mut stick_y: f32 = 0.0;
+    for gamepad in gamepads.iter() {
+        let just_pressed =
+            |button_type| buttons.just_pressed(GamepadButton::new(gamepad, button_type));
+        if just_pressed(GamepadButtonType::DPadUp) {
+            step -= 1;
+        }
+        if just_pressed(GamepadButtonType::DPadDown) {
+            step += 1;
+        }
+        activate |= just_pressed(GamepadButtonType::South);
+
+        let y = axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+            .unwrap_or(0.0);
+        if y.abs() > stick_y.abs() {
+            stick_y = y;
+        }
+    }
+
+    // The stick only moves one button each time it's pushed
+    if stick_y.abs() > STICK_THRESHOLD {
+        if !*stick_held {
+            step -= stick_y.signum() as isize;
+        }
+        *stick_held = true;
+    } else {
+        *stick_held = false;
+    }
+
+    if step != 0 {
+        let len = MENU_ORDER.len() as isize;
+        focus.0 = Some(match focus.0 {
+            Some(index) => (index as isize + step).rem_euclid(len) as usize,
+            None if step > 0 => 0,
+            None => MENU_ORDER.len() - 1,
+        });
+    }
+
+    let Some(index) = focus.0 else { return; };
+    if activate {
+        for mut menu in &mut menus {
+            menu.press(MENU_ORDER[index]);
+        }
+    }
+}
+
 fn despawn_menu(mut commands: Commands, menus: Query<Entity, With<MainMenuRoot>>) {
     for e in &menus {
         commands.entity(e).despawn_recursive();
@@ -133,10 +315,19 @@ fn despawn_menu(mut commands: Commands, menus: Query<Entity, With<MainMenuRoot>>
 }
 
 fn highlight(
-    mut buttons: Query<(&mut BackgroundColor, &Interaction), (With<Button>, Changed<Interaction>)>,
+    mut buttons: Query<
+        (
+            &ActionStateDriver<MainMenuActions>,
+            &mut BackgroundColor,
+            &Interaction,
+        ),
+        With<Button>,
+    >,
+    focus: Res<MenuFocus>,
 ) {
-    for (mut color, interaction) in &mut buttons {
-        if let Interaction::Hovered = *interaction {
+    let focused = focus.0.map(|index| MENU_ORDER[index]);
+    for (driver, mut color, interaction) in &mut buttons {
+        if matches!(interaction, Interaction::Hovered) || focused == Some(driver.action) {
             *color = BackgroundColor(Color::GRAY);
         } else {
             *color = BackgroundColor(Color::DARK_GRAY);
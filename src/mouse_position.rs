@@ -1,40 +1,157 @@
 use bevy::prelude::*;
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::ActionState};
 
-use crate::init::MainCamera;
+use crate::{
+    init::MainCamera,
+    player::{Player, PlayerActions},
+    state::{OnEnterRun, OnExitRun},
+};
 
 pub struct MousePositionPlugin;
 
 impl Plugin for MousePositionPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<MousePosition>()
+            .register_type::<AimSource>()
             .init_resource::<MousePosition>()
-            .add_system(my_cursor_system.in_base_set(CoreSet::PreUpdate));
+            .init_resource::<AimSource>()
+            .init_resource::<GamepadAim>()
+            .add_systems(
+                (my_cursor_system, aim_with_gamepad)
+                    .chain()
+                    .after(InputManagerSystem::Update)
+                    .after(InputManagerSystem::ManualControl)
+                    .in_base_set(CoreSet::PreUpdate),
+            )
+            .add_system(spawn_reticle.in_schedule(OnEnterRun))
+            .add_system(move_reticle)
+            .add_system(despawn_reticle.in_schedule(OnExitRun));
     }
 }
 
+/// Where the player is aiming in world coordinates, following the cursor or the right stick.
 #[derive(Resource, Clone, Default, Debug, Deref, DerefMut, Reflect, FromReflect)]
 #[reflect(Resource, Default, Debug)]
 pub struct MousePosition(pub Vec2);
 
+/// Whichever of the mouse or the right stick was used last drives [`MousePosition`].
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq, Reflect, FromReflect)]
+#[reflect(Resource, Default, Debug)]
+pub enum AimSource {
+    #[default]
+    Mouse,
+    Gamepad,
+}
+
+/// Last direction the right stick was pushed in, kept when it's released so the aim point keeps
+/// following the player.
+#[derive(Resource, Clone, Debug)]
+struct GamepadAim(Vec2);
+
+impl Default for GamepadAim {
+    fn default() -> Self {
+        Self(Vec2::X)
+    }
+}
+
+/// Distance from the player to the aim point when aiming with the right stick
+const GAMEPAD_AIM_DISTANCE: f32 = 64.0;
+
+#[derive(Component, Default, Debug)]
+struct AimReticle;
+
 fn my_cursor_system(
     windows: Query<&Window>,
     camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     mut mouse_position: ResMut<MousePosition>,
+    mut aim_source: ResMut<AimSource>,
+    mut last_cursor: Local<Option<Vec2>>,
 ) {
+    // get the window that the camera is displaying to (or the primary window)
+    // there is none when running headless
+    let Ok(window) = windows.get_single() else { return; };
+
+    // switch back from the gamepad once the mouse moves
+    let cursor = window.cursor_position();
+    if cursor != *last_cursor {
+        *last_cursor = cursor;
+        if *aim_source != AimSource::Mouse {
+            *aim_source = AimSource::Mouse;
+        }
+    }
+    if *aim_source != AimSource::Mouse {
+        return;
+    }
+
     // get the camera info and transform
     // assuming there is exactly one main camera entity, so query::single() is OK
     let Ok((camera, camera_transform)) = camera_q.get_single() else { return; };
 
-    // get the window that the camera is displaying to (or the primary window)
-    let window = windows.single();
-
     // check if the cursor is inside the window and get its position
     // then, ask bevy to convert into world coordinates, and truncate to discard Z
-    if let Some(world_position) = window
-        .cursor_position()
+    if let Some(world_position) = cursor
         .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
         .map(|ray| ray.origin.truncate())
     {
         **mouse_position = world_position;
     }
 }
+
+fn aim_with_gamepad(
+    players: Query<(&Transform, &ActionState<PlayerActions>), With<Player>>,
+    mut gamepad_aim: ResMut<GamepadAim>,
+    mut mouse_position: ResMut<MousePosition>,
+    mut aim_source: ResMut<AimSource>,
+) {
+    let Ok((transform, action)) = players.get_single() else { return; };
+
+    let stick = action
+        .axis_pair(PlayerActions::Aim)
+        .map_or(Vec2::ZERO, |axis| axis.xy());
+    if let Some(direction) = stick.try_normalize() {
+        gamepad_aim.0 = direction;
+        *aim_source = AimSource::Gamepad;
+    }
+    if *aim_source != AimSource::Gamepad {
+        return;
+    }
+
+    **mouse_position = transform.translation.truncate() + gamepad_aim.0 * GAMEPAD_AIM_DISTANCE;
+}
+
+fn spawn_reticle(mut commands: Commands) {
+    commands.spawn((
+        AimReticle,
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::WHITE.with_a(0.8),
+                custom_size: Some(Vec2::splat(3.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 900.0),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+    ));
+}
+
+/// The cursor already shows where the mouse is aiming, so the reticle is only shown for gamepads.
+fn move_reticle(
+    mut reticles: Query<(&mut Transform, &mut Visibility), With<AimReticle>>,
+    mouse_position: Res<MousePosition>,
+    aim_source: Res<AimSource>,
+) {
+    for (mut transform, mut visibility) in &mut reticles {
+        transform.translation = mouse_position.extend(transform.translation.z);
+        *visibility = match *aim_source {
+            AimSource::Mouse => Visibility::Hidden,
+            AimSource::Gamepad => Visibility::Visible,
+        };
+    }
+}
+
+fn despawn_reticle(mut commands: Commands, reticles: Query<Entity, With<AimReticle>>) {
+    for e in &reticles {
+        commands.entity(e).despawn_recursive();
+    }
+}
@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    prelude::{ActionState, ActionStateDriver, InputManagerPlugin, InputMap},
+    Actionlike, InputManagerBundle,
+};
+
+use crate::{
+    assets::GameAssets,
+    player::{Player, PlayerActions},
+    settings_screen::SettingsScreenState,
+    state::GameState,
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(InputManagerPlugin::<PauseMenuActions>::default())
+            .add_system(open_pause_menu.in_set(OnUpdate(GameState::Playing)))
+            .add_systems((freeze_world, spawn_menu).in_schedule(OnEnter(GameState::Paused)))
+            .add_system(
+                handle_actions
+                    .run_if(in_state(SettingsScreenState::Closed))
+                    .in_set(OnUpdate(GameState::Paused)),
+            )
+            .add_system(highlight.in_set(OnUpdate(GameState::Paused)))
+            .add_systems((unfreeze_world, despawn_menu).in_schedule(OnExit(GameState::Paused)))
+            .add_system(restart.in_schedule(OnEnter(GameState::Restarting)));
+    }
+}
+
+#[derive(Component, Default, Debug)]
+struct PauseMenuRoot;
+
+#[derive(Actionlike, Component, PartialEq, Eq, Clone, Copy, Hash, Debug)]
+enum PauseMenuActions {
+    Resume,
+    Settings,
+    Restart,
+    Quit,
+}
+
+fn open_pause_menu(
+    player: Query<&ActionState<PlayerActions>, With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for action in &player {
+        if action.just_pressed(PlayerActions::Pause) {
+            next_state.set(GameState::Paused);
+        }
+    }
+}
+
+/// No steps are taken while paused, this stops what runs every frame, like damage numbers and
+/// screen shake.
+fn freeze_world(mut time: ResMut<Time>) {
+    time.pause();
+}
+
+fn unfreeze_world(mut time: ResMut<Time>) {
+    time.unpause();
+}
+
+fn spawn_menu(mut commands: Commands, assets: Res<GameAssets>) {
+    let text_style = TextStyle {
+        font: assets.font_normal.clone(),
+        font_size: 48.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            PauseMenuRoot,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    gap: Size::new(Val::Auto, Val::Px(20.0)),
+                    ..Default::default()
+                },
+                background_color: Color::BLACK.with_a(0.7).into(),
+                z_index: ZIndex::Global(1),
+                ..Default::default()
+            },
+            InputManagerBundle::<PauseMenuActions> {
+                input_map: InputMap::default(),
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font: assets.font_italic.clone(),
+                    font_size: 64.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            let parent_id = parent.parent_entity();
+            for (action, label) in [
+                (PauseMenuActions::Resume, "Resume"),
+                (PauseMenuActions::Settings, "Settings"),
+                (PauseMenuActions::Restart, "Restart"),
+                (PauseMenuActions::Quit, "Quit to menu"),
+            ] {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                justify_content: JustifyContent::Center,
+                                size: Size::new(Val::Px(300.0), Val::Px(50.0)),
+                                ..Default::default()
+                            },
+                            background_color: Color::DARK_GRAY.into(),
+                            ..Default::default()
+                        },
+                        ActionStateDriver {
+                            action,
+                            entity: parent_id,
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                    });
+            }
+        });
+}
+
+fn handle_actions(
+    menu: Query<&ActionState<PauseMenuActions>>,
+    player: Query<&ActionState<PlayerActions>, With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_settings_state: ResMut<NextState<SettingsScreenState>>,
+) {
+    for input in &menu {
+        if input.just_pressed(PauseMenuActions::Resume) {
+            next_state.set(GameState::Playing);
+        }
+        if input.just_pressed(PauseMenuActions::Settings) {
+            next_settings_state.set(SettingsScreenState::Open);
+        }
+        if input.just_pressed(PauseMenuActions::Restart) {
+            next_state.set(GameState::Restarting);
+        }
+        if input.just_pressed(PauseMenuActions::Quit) {
+            next_state.set(GameState::MainMenu);
+        }
+    }
+    for action in &player {
+        if action.just_pressed(PlayerActions::Pause) {
+            next_state.set(GameState::Playing);
+        }
+    }
+}
+
+fn restart(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+fn despawn_menu(mut commands: Commands, menus: Query<Entity, With<PauseMenuRoot>>) {
+    for e in &menus {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn highlight(
+    mut buttons: Query<(&mut BackgroundColor, &Interaction), (With<Button>, Changed<Interaction>)>,
+) {
+    for (mut color, interaction) in &mut buttons {
+        if let Interaction::Hovered = *interaction {
+            *color = BackgroundColor(Color::GRAY);
+        } else {
+            *color = BackgroundColor(Color::DARK_GRAY);
+        }
+    }
+}
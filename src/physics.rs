@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use bevy_rapier2d::{
+    plugin::systems::sync_removals,
+    prelude::{NoUserData, PhysicsSet, RapierConfiguration, RapierPhysicsPlugin, TimestepMode},
+};
+
+use crate::timestep::{AddToStep, StepSet, TIMESTEP};
+
+/// Top down physics, so without gravity, stepped once per [`TIMESTEP`] in
+/// [`crate::timestep::GameStep`].
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(32.0)
+                .with_default_system_setup(false),
+        )
+        .insert_resource(RapierConfiguration {
+            gravity: Vec2::ZERO,
+            timestep_mode: TimestepMode::Fixed {
+                dt: TIMESTEP.as_secs_f32(),
+                substeps: 1,
+            },
+            ..Default::default()
+        })
+        .configure_step_sets(
+            (
+                PhysicsSet::SyncBackend,
+                PhysicsSet::SyncBackendFlush,
+                PhysicsSet::StepSimulation,
+                PhysicsSet::Writeback,
+            )
+                .chain()
+                .after(StepSet::UpdateFlush)
+                .before(StepSet::PostUpdate),
+        )
+        .add_step_systems(
+            RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend)
+                .in_base_set(PhysicsSet::SyncBackend),
+        )
+        .add_step_systems(
+            RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackendFlush)
+                .in_base_set(PhysicsSet::SyncBackendFlush),
+        )
+        // These also update the collision and contact force events, so like the step events they
+        // last two steps however many frames pass in between
+        .add_step_systems(
+            RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::StepSimulation)
+                .in_base_set(PhysicsSet::StepSimulation),
+        )
+        .add_step_systems(
+            RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::Writeback)
+                .in_base_set(PhysicsSet::Writeback),
+        )
+        // The app forgets removed components after two frames, which can pass without a step
+        .add_system(sync_removals.in_base_set(CoreSet::Last));
+    }
+}
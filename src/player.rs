@@ -3,20 +3,28 @@ use bevy_rapier2d::prelude::{
     Collider, Damping, ExternalForce, ExternalImpulse, LockedAxes, RigidBody, Velocity,
 };
 use leafwing_input_manager::{
+    buttonlike::ButtonState,
+    plugin::InputManagerSystem,
     prelude::{ActionState, InputManagerPlugin, InputMap},
     Actionlike, InputManagerBundle,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
     abilities::{
-        cooldown::{AbilityCooldown, AbilityCooldownTime},
-        heat::{AddHeatOnUse, Heat},
-        Loadout, Power, SideEffect, UseAbilityEvent,
+        activation::{AbilityTriggerEvent, ActivationSet, TriggerState},
+        cooldown::AbilityCooldown,
+        heat::Heat,
+        Loadout,
     },
     assets::GameAssets,
     enemy::Target,
-    health::{DeathEvent, Health, MaxHealth},
-    state::GameState,
+    health::{DeathEvent, Health, IFrames, MaxHealth, Shield},
+    room::RoomSet,
+    settings::Settings,
+    state::{GameState, OnEnterRun, OnExitRun, RunTransitionSet},
+    status::StatusSet,
+    timestep::{AddToStep, StepSet},
 };
 
 pub struct PlayerPlugin;
@@ -25,19 +33,38 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Player>()
             .add_plugin(InputManagerPlugin::<PlayerActions>::default())
-            .add_system(on_death.in_set(OnUpdate(GameState::Playing)))
-            .add_systems(
+            .init_resource::<LatchedInputs>()
+            .add_system(
+                latch_inputs
+                    .after(InputManagerSystem::Update)
+                    .in_base_set(CoreSet::PreUpdate),
+            )
+            .add_step_system(
+                apply_latched_inputs
+                    .in_set(PlayerInputSet)
+                    .after(RunTransitionSet)
+                    .in_base_set(StepSet::First),
+            )
+            .add_step_system(
+                on_death
+                    .after(RoomSet::Spawn)
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_step_systems(
                 (select_ability, use_ability, move_player, rotate_sprite)
                     .chain()
+                    .before(ActivationSet)
+                    .before(StatusSet)
                     .in_set(OnUpdate(GameState::Playing)),
             )
-            .add_system(cleanup.in_schedule(OnExit(GameState::Playing)))
-            .add_system(spawn_player.in_schedule(OnEnter(GameState::Playing)));
+            .add_system(update_bindings)
+            .add_system(cleanup.in_schedule(OnExitRun))
+            .add_system(spawn_player.in_schedule(OnEnterRun));
     }
 }
 
-#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug)]
-enum PlayerActions {
+#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Serialize, Deserialize)]
+pub enum PlayerActions {
     Left,
     Right,
     Up,
@@ -47,56 +74,57 @@ enum PlayerActions {
     Ability3,
     Ability4,
     UseAbility,
+    Pause,
+    PreviousAbility,
+    NextAbility,
+    /// Left stick
+    Move,
+    /// Right stick
+    Aim,
+}
+
+impl PlayerActions {
+    /// Stick actions only have gamepad bindings, and can't be rebound.
+    pub fn is_axis(self) -> bool {
+        matches!(self, PlayerActions::Move | PlayerActions::Aim)
+    }
 }
 
 #[derive(Component, Clone, Default, Debug, Reflect, FromReflect)]
 #[reflect(Component, Default, Debug)]
 pub struct Player;
 
-fn spawn_player(mut commands: Commands, assets: Res<GameAssets>) {
+/// Gives the inputs of the frames since the last step to the player's [`ActionState`], at the
+/// start of the step. Replays and the bot replace them after this.
+#[derive(SystemSet, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct PlayerInputSet;
+
+/// Bit `i` is set if the [`PlayerActions`] with index `i` was pressed or released in a frame since
+/// the last step, so presses between two steps aren't lost.
+#[derive(Resource, Clone, Copy, Default, Debug)]
+struct LatchedInputs {
+    pressed: u32,
+    released: u32,
+    /// Held down in the last frame
+    held: u32,
+}
+
+const PLAYER_IFRAMES: f32 = 0.5;
+const PLAYER_SHIELD: f32 = 25.0;
+const PLAYER_SHIELD_RECHARGE_RATE: f32 = 10.0;
+const PLAYER_SHIELD_RECHARGE_DELAY: f32 = 3.0;
+
+fn spawn_player(mut commands: Commands, assets: Res<GameAssets>, settings: Res<Settings>) {
     let ability = commands
         .spawn((
-            Power::Teleport,
-            SideEffect::InvisibleWithShadow,
+            assets.teleport_ability.clone(),
             // Workaround so that it doesn't fire at the start
             AbilityCooldown::default(),
-            AbilityCooldownTime(5.0),
         ))
         .id();
-    let ability2 = commands
-        .spawn((
-            Power::Fireball,
-            AbilityCooldownTime(1.0),
-            AddHeatOnUse(40.0),
-        ))
-        .id();
-    let ability3 = commands
-        .spawn((
-            Power::IceWall,
-            SideEffect::CoolZone,
-            AbilityCooldownTime(3.0),
-        ))
-        .id();
-    let ability4 = commands
-        .spawn((
-            Power::Shotgun,
-            SideEffect::TakeDamage,
-            AbilityCooldownTime(0.1),
-        ))
-        .id();
-    let mut input_map = InputMap::new([
-        (KeyCode::A, PlayerActions::Left),
-        (KeyCode::S, PlayerActions::Down),
-        (KeyCode::D, PlayerActions::Right),
-        (KeyCode::W, PlayerActions::Up),
-        (KeyCode::Key1, PlayerActions::Ability1),
-        (KeyCode::Key2, PlayerActions::Ability2),
-        (KeyCode::Key3, PlayerActions::Ability3),
-        (KeyCode::Key4, PlayerActions::Ability4),
-    ]);
-
-    input_map.insert(MouseButton::Left, PlayerActions::UseAbility);
-
+    let ability2 = commands.spawn(assets.fireball_ability.clone()).id();
+    let ability3 = commands.spawn(assets.ice_wall_ability.clone()).id();
+    let ability4 = commands.spawn(assets.shotgun_ability.clone()).id();
     commands.spawn((
         (
             Player,
@@ -112,7 +140,7 @@ fn spawn_player(mut commands: Commands, assets: Res<GameAssets>) {
             },
         ),
         InputManagerBundle::<PlayerActions> {
-            input_map,
+            input_map: settings.bindings.input_map(),
             ..Default::default()
         },
         RigidBody::Dynamic,
@@ -128,18 +156,109 @@ fn spawn_player(mut commands: Commands, assets: Res<GameAssets>) {
         Collider::ball(8.0),
         ExternalForce::default(),
         ExternalImpulse::default(),
-        Health(100.0),
-        MaxHealth(100.0),
+        (
+            Health(100.0),
+            MaxHealth(100.0),
+            IFrames(PLAYER_IFRAMES),
+            Shield::new(
+                PLAYER_SHIELD,
+                PLAYER_SHIELD_RECHARGE_RATE,
+                PLAYER_SHIELD_RECHARGE_DELAY,
+            ),
+        ),
         CurrentAbility(0),
         Heat::default(),
     ));
 }
 
+/// Presses in menus, like clicking a draft card, are left out.
+fn latch_inputs(
+    players: Query<&ActionState<PlayerActions>, With<Player>>,
+    state: Res<State<GameState>>,
+    mut latched: ResMut<LatchedInputs>,
+) {
+    for action_state in &players {
+        latched.held = 0;
+        for action in PlayerActions::variants() {
+            let bit = 1 << action.index();
+            if action_state.pressed(action) {
+                latched.held |= bit;
+            }
+            if state.0 != GameState::Playing {
+                continue;
+            }
+            if action_state.just_pressed(action) {
+                latched.pressed |= bit;
+            }
+            if action_state.just_released(action) {
+                latched.released |= bit;
+            }
+        }
+    }
+}
+
+/// Pausing is left to the pause menu, which sees every frame.
+fn apply_latched_inputs(
+    mut players: Query<&mut ActionState<PlayerActions>, With<Player>>,
+    mut latched: ResMut<LatchedInputs>,
+) {
+    let mut tapped = 0;
+    for mut action_state in &mut players {
+        for action in PlayerActions::variants() {
+            if action == PlayerActions::Pause {
+                continue;
+            }
+            let bit = 1 << action.index();
+            let pressed = latched.held & bit != 0;
+            let state = if latched.pressed & bit != 0 {
+                if !pressed {
+                    tapped |= bit;
+                }
+                ButtonState::JustPressed
+            } else if pressed {
+                ButtonState::Pressed
+            } else if latched.released & bit != 0 {
+                ButtonState::JustReleased
+            } else {
+                ButtonState::Released
+            };
+            action_state.action_data_mut(action).state = state;
+        }
+    }
+    // Released already, which the next step gets to see
+    latched.pressed = 0;
+    latched.released = tapped;
+}
+
+/// Rebinding controls from the pause menu applies right away.
+fn update_bindings(
+    settings: Res<Settings>,
+    mut players: Query<&mut InputMap<PlayerActions>, With<Player>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut input_map in &mut players {
+        let bindings = settings.bindings.input_map();
+        if *input_map != bindings {
+            *input_map = bindings;
+        }
+    }
+}
+
 fn move_player(
     mut players: Query<(&mut ExternalForce, &ActionState<PlayerActions>), With<Player>>,
 ) {
     const PLAYER_MOVE_FORCE: f32 = 64.0;
     for (mut force, action) in &mut players {
+        let stick = action
+            .clamped_axis_pair(PlayerActions::Move)
+            .map_or(Vec2::ZERO, |axis| axis.xy());
+        if stick != Vec2::ZERO {
+            force.force = stick.clamp_length_max(1.0) * PLAYER_MOVE_FORCE;
+            continue;
+        }
+
         let mut new_force = Vec2::ZERO;
         if action.pressed(PlayerActions::Left) {
             new_force.x = -1.0;
@@ -161,9 +280,10 @@ fn move_player(
 pub struct CurrentAbility(pub usize);
 
 fn select_ability(
-    mut players: Query<(&mut CurrentAbility, &ActionState<PlayerActions>), With<Player>>,
+    mut players: Query<(&mut CurrentAbility, &Loadout, &ActionState<PlayerActions>), With<Player>>,
 ) {
-    for (mut current_ability, action) in &mut players {
+    for (mut current_ability, loadout, action) in &mut players {
+        let count = loadout.abilities.len().max(1);
         if action.just_pressed(PlayerActions::Ability1) {
             current_ability.0 = 0;
         } else if action.just_pressed(PlayerActions::Ability2) {
@@ -172,6 +292,10 @@ fn select_ability(
             current_ability.0 = 2;
         } else if action.just_pressed(PlayerActions::Ability4) {
             current_ability.0 = 3;
+        } else if action.just_pressed(PlayerActions::NextAbility) {
+            current_ability.0 = (current_ability.0 + 1) % count;
+        } else if action.just_pressed(PlayerActions::PreviousAbility) {
+            current_ability.0 = (current_ability.0 + count - 1) % count;
         }
     }
 }
@@ -181,15 +305,24 @@ fn use_ability(
         (Entity, &CurrentAbility, &ActionState<PlayerActions>),
         (With<Player>, With<Loadout>),
     >,
-    mut use_ability: EventWriter<UseAbilityEvent>,
+    mut triggers: EventWriter<AbilityTriggerEvent>,
 ) {
     for (player_entity, current_ability, action) in &players {
-        if action.just_pressed(PlayerActions::UseAbility) {
-            use_ability.send(UseAbilityEvent {
-                loadout: player_entity,
-                ability: current_ability.0,
-            });
-        }
+        let state = if action.just_pressed(PlayerActions::UseAbility) {
+            TriggerState::Pressed
+        } else if action.just_released(PlayerActions::UseAbility) {
+            TriggerState::Released
+        } else if action.pressed(PlayerActions::UseAbility) {
+            TriggerState::Held
+        } else {
+            continue;
+        };
+
+        triggers.send(AbilityTriggerEvent {
+            loadout: player_entity,
+            ability: current_ability.0,
+            state,
+        });
     }
 }
 
@@ -212,8 +345,11 @@ fn on_death(
     }
 }
 
-fn cleanup(mut commands: Commands, player: Query<Entity, With<Player>>) {
-    for e in &player {
+fn cleanup(mut commands: Commands, player: Query<(Entity, &Loadout), With<Player>>) {
+    for (e, loadout) in &player {
+        for &ability in &loadout.abilities {
+            commands.entity(ability).despawn_recursive();
+        }
         commands.entity(e).despawn_recursive();
     }
 }
@@ -0,0 +1,225 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier2d::prelude::{CollisionEvent, QueryFilter, RapierContext, Velocity};
+
+use crate::{
+    abilities::wall::IceWall,
+    arena::Arena,
+    enemy::{Enemy, EnemySet},
+    explosion::{ExplosionEvent, HandleExplosionSet},
+    health::{DamageEvent, DamageType, HandleDamageSet, Health},
+    status::{ApplyStatusEvent, StatusEffect},
+    timestep::{AddToStep, StepSet},
+};
+
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Projectile>()
+            .register_type::<Homing>()
+            .register_type::<ProjectileExplosion>()
+            .configure_step_set(ProjectileSet.after(EnemySet::Attack))
+            .add_step_systems(
+                (home_projectiles, bounce_projectiles)
+                    .chain()
+                    .in_set(ProjectileSet)
+                    .before(HandleExplosionSet),
+            )
+            // After the physics, so it handles the collisions of the step they happen in
+            .add_step_system(
+                hit_targets
+                    .in_base_set(StepSet::PostUpdate)
+                    .before(HandleDamageSet),
+            );
+    }
+}
+
+#[derive(SystemSet, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct ProjectileSet;
+
+/// Extra distance checked for walls in front of a projectile, so it turns around before its
+/// collider touches them.
+const WALL_MARGIN: f32 = 2.0;
+
+/// Damages whatever it hits, apart from its owner. Needs a dynamic [`RigidBody`], a sensor
+/// [`Collider`] with collision events enabled, and a [`Velocity`].
+///
+/// [`RigidBody`]: bevy_rapier2d::prelude::RigidBody
+/// [`Collider`]: bevy_rapier2d::prelude::Collider
+#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct Projectile {
+    pub damage: f32,
+    pub owner: Option<Entity>,
+    /// Number of targets it passes through before being destroyed
+    pub pierce: u32,
+    /// Number of times it bounces off walls before being destroyed
+    pub bounces: u32,
+    pub homing: Option<Homing>,
+    pub explosion: Option<ProjectileExplosion>,
+    /// Applied to every target it hits
+    pub effects: Vec<StatusEffect>,
+}
+
+/// Steers a projectile towards the closest [`Enemy`].
+#[derive(Clone, Debug, Default, Reflect, FromReflect)]
+pub struct Homing {
+    pub range: f32,
+    /// Radians per second
+    pub turn_rate: f32,
+}
+
+/// An explosion triggered every time the projectile hits a target, and when it's destroyed by a
+/// wall. Taken out of the projectile once it's destroyed, so it can't explode twice.
+#[derive(Clone, Debug, Default, Reflect, FromReflect)]
+pub struct ProjectileExplosion {
+    pub range: f32,
+    pub force: f32,
+    pub damage: f32,
+    pub effects: Vec<StatusEffect>,
+}
+
+fn home_projectiles(
+    mut projectiles: Query<(&Projectile, &Transform, &mut Velocity)>,
+    enemies: Query<&Transform, With<Enemy>>,
+    time: Res<Time>,
+) {
+    for (projectile, projectile_transform, mut velocity) in &mut projectiles {
+        let Some(homing) = &projectile.homing else { continue; };
+        let projectile_position = projectile_transform.translation.truncate();
+
+        let closest_enemy = enemies
+            .iter()
+            .map(|enemy_transform| enemy_transform.translation.truncate())
+            .filter(|enemy_position| enemy_position.distance(projectile_position) <= homing.range)
+            .min_by(|a, b| {
+                a.distance_squared(projectile_position)
+                    .total_cmp(&b.distance_squared(projectile_position))
+            });
+        let Some(enemy_position) = closest_enemy else { continue; };
+
+        let angle = velocity
+            .linvel
+            .angle_between(enemy_position - projectile_position);
+        if angle.is_nan() {
+            continue;
+        }
+        let max_turn = homing.turn_rate * time.delta_seconds();
+        let turn = Vec2::from_angle(angle.clamp(-max_turn, max_turn));
+        velocity.linvel = turn.rotate(velocity.linvel);
+    }
+}
+
+fn bounce_projectiles(
+    mut commands: Commands,
+    mut projectiles: Query<(Entity, &mut Projectile, &Transform, &mut Velocity)>,
+    walls: Query<(), Or<(With<IceWall>, With<Arena>)>>,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+    mut explosion_events: EventWriter<ExplosionEvent>,
+) {
+    let is_wall = |entity| walls.contains(entity);
+    let filter = QueryFilter::only_fixed().predicate(&is_wall);
+
+    for (projectile_id, mut projectile, projectile_transform, mut velocity) in &mut projectiles {
+        let speed = velocity.linvel.length();
+        let Some(direction) = velocity.linvel.try_normalize() else { continue; };
+        let position = projectile_transform.translation.truncate();
+
+        let max_distance = speed * time.delta_seconds() + WALL_MARGIN;
+
+        let hit = rapier_context.cast_ray_and_get_normal(
+            position,
+            direction,
+            max_distance,
+            false,
+            filter,
+        );
+        let Some((_, hit)) = hit else { continue; };
+
+        if projectile.bounces == 0 {
+            if let Some(explosion) = projectile.explosion.take() {
+                explosion_events.send(ExplosionEvent {
+                    position: hit.point,
+                    range: explosion.range,
+                    force: explosion.force,
+                    damage: explosion.damage,
+                    effects: explosion.effects,
+                    source: projectile.owner,
+                });
+            }
+            commands.entity(projectile_id).despawn_recursive();
+            continue;
+        }
+        projectile.bounces -= 1;
+        let reflection = 2.0 * velocity.linvel.dot(hit.normal) * hit.normal;
+        velocity.linvel -= reflection;
+    }
+}
+
+fn hit_targets(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut projectiles: Query<(&mut Projectile, &Transform)>,
+    targets: Query<(), With<Health>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut explosion_events: EventWriter<ExplosionEvent>,
+    mut status_events: EventWriter<ApplyStatusEvent>,
+    mut destroyed: Local<HashSet<Entity>>,
+) {
+    destroyed.clear();
+    for collision in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision else { continue; };
+
+        for (projectile_id, target_id) in [(*e1, *e2), (*e2, *e1)] {
+            let Ok((mut projectile, transform)) = projectiles.get_mut(projectile_id) else { continue; };
+            if targets.get(target_id).is_err()
+                || projectile.owner == Some(target_id)
+                || destroyed.contains(&projectile_id)
+            {
+                continue;
+            }
+
+            // Explosion-only projectiles would still flash and knock back what they hit
+            if projectile.damage > 0.0 {
+                damage_events.send(DamageEvent {
+                    damaged_id: target_id,
+                    damage: projectile.damage,
+                    source: projectile.owner,
+                    damage_type: DamageType::Ballistic,
+                    hit: true,
+                });
+            }
+            for effect in &projectile.effects {
+                status_events.send(ApplyStatusEvent {
+                    target: target_id,
+                    effect: StatusEffect {
+                        source: projectile.owner,
+                        ..effect.clone()
+                    },
+                });
+            }
+            let explosion = match projectile.pierce {
+                0 => projectile.explosion.take(),
+                _ => projectile.explosion.clone(),
+            };
+            if let Some(explosion) = explosion {
+                explosion_events.send(ExplosionEvent {
+                    position: transform.translation.truncate(),
+                    range: explosion.range,
+                    force: explosion.force,
+                    damage: explosion.damage,
+                    effects: explosion.effects,
+                    source: projectile.owner,
+                });
+            }
+
+            if projectile.pierce == 0 {
+                commands.entity(projectile_id).despawn_recursive();
+                destroyed.insert(projectile_id);
+            } else {
+                projectile.pierce -= 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    axislike::DualAxisData, buttonlike::ButtonState, prelude::ActionState, Actionlike,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    draft::DraftChoice,
+    mouse_position::MousePosition,
+    player::{Player, PlayerActions, PlayerInputSet},
+    seed::{NextRunSeed, RunSeed, SeedSet},
+    state::{GameState, OnEnterRun, OnExitRun, RunTransitionSet},
+    timestep::{AddToStep, StepSet},
+};
+
+/// Records the inputs of every run, and plays back a [`ReplayPlayback`] instead of the player's
+/// inputs. Together with the [`RunSeed`] they reproduce the run step by step.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunRecording>()
+            .add_step_system(
+                start_playback
+                    .run_if(resource_added::<ReplayPlayback>())
+                    .in_set(OnUpdate(GameState::MainMenu)),
+            )
+            .add_system(start_recording.in_schedule(OnEnterRun).after(SeedSet))
+            .add_step_system(
+                play_back_inputs
+                    .run_if(resource_exists::<ReplayPlayback>())
+                    .run_if(is_recorded)
+                    .after(RunTransitionSet)
+                    .after(PlayerInputSet)
+                    .in_base_set(StepSet::First),
+            )
+            .add_step_system(
+                record_inputs
+                    .run_if(is_recorded)
+                    .in_base_set(StepSet::PostUpdate),
+            )
+            .add_system(stop_playback.in_schedule(OnExitRun));
+    }
+}
+
+/// Inputs of a single step.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// Bit `i` is set if the [`PlayerActions`] with index `i` is pressed
+    pub pressed: u32,
+    /// Bit `i` is set if the [`PlayerActions`] with index `i` was pressed or released this step
+    pub just_changed: u32,
+    /// The left stick, zero without a gamepad
+    pub movement: Vec2,
+    pub mouse_position: Vec2,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draft_choice: Option<DraftChoice>,
+}
+
+impl ReplayFrame {
+    fn record(
+        action_state: &ActionState<PlayerActions>,
+        mouse_position: &MousePosition,
+        draft_choice: Option<DraftChoice>,
+    ) -> Self {
+        let mut frame = Self {
+            movement: action_state
+                .axis_pair(PlayerActions::Move)
+                .map_or(Vec2::ZERO, |axis| axis.xy()),
+            mouse_position: **mouse_position,
+            draft_choice,
+            ..Default::default()
+        };
+        for action in PlayerActions::variants() {
+            let bit = 1 << action.index();
+            if action_state.pressed(action) {
+                frame.pressed |= bit;
+            }
+            if action_state.just_pressed(action) || action_state.just_released(action) {
+                frame.just_changed |= bit;
+            }
+        }
+        frame
+    }
+
+    /// Pausing is left to the viewer, so a replay can be paused and quit like a run.
+    fn apply(&self, action_state: &mut ActionState<PlayerActions>) {
+        for action in PlayerActions::variants() {
+            if action == PlayerActions::Pause {
+                continue;
+            }
+            let bit = 1 << action.index();
+            let pressed = self.pressed & bit != 0;
+            let just_changed = self.just_changed & bit != 0;
+
+            action_state.action_data_mut(action).state = match (pressed, just_changed) {
+                (true, true) => ButtonState::JustPressed,
+                (true, false) => ButtonState::Pressed,
+                (false, true) => ButtonState::JustReleased,
+                (false, false) => ButtonState::Released,
+            };
+        }
+        action_state.action_data_mut(PlayerActions::Move).axis_pair =
+            (self.movement != Vec2::ZERO).then(|| DualAxisData::from_xy(self.movement));
+    }
+}
+
+/// The seed and inputs of a run, stored as runs of identical frames to keep the files small.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: RunSeed,
+    frames: Vec<(u32, ReplayFrame)>,
+}
+
+impl Replay {
+    pub fn new(seed: RunSeed) -> Self {
+        Self {
+            seed,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Adds the inputs of the next step.
+    pub fn push(&mut self, frame: ReplayFrame) {
+        match self.frames.last_mut() {
+            Some((count, last)) if *last == frame => *count += 1,
+            _ => self.frames.push((1, frame)),
+        }
+    }
+
+    /// Number of steps recorded.
+    pub fn len(&self) -> usize {
+        self.frames.iter().map(|&(count, _)| count as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The inputs of every step in order.
+    pub fn frames(&self) -> impl Iterator<Item = &ReplayFrame> {
+        self.frames
+            .iter()
+            .flat_map(|(count, frame)| std::iter::repeat_n(frame, *count as usize))
+    }
+
+    /// Reads a replay file, logging why if it can't.
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!("Couldn't read replay {}: {error}", path.display());
+                return None;
+            }
+        };
+
+        match ron::from_str(&contents) {
+            Ok(replay) => Some(replay),
+            Err(error) => {
+                warn!("Invalid replay in {}: {error}", path.display());
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        let contents = match ron::to_string(self) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!("Couldn't serialize replay: {error}");
+                return;
+            }
+        };
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, contents));
+        if let Err(error) = result {
+            warn!("Couldn't save replay to {}: {error}", path.display());
+        }
+    }
+
+    /// Where the client keeps the replay of the last run, there is none on the web.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn last_run_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("labrats").join("last_run.ron"))
+    }
+
+    #[cfg(target_family = "wasm")]
+    pub fn last_run_path() -> Option<PathBuf> {
+        None
+    }
+}
+
+/// The replay of the current or last run.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct RunRecording(pub Replay);
+
+/// Insert in the main menu to watch a replay. It starts a run with the replay's seed, and goes
+/// back to the main menu once the inputs run out.
+#[derive(Resource, Clone, Debug)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    /// Index of the current run of frames, and how many steps of it were played
+    run: usize,
+    played: u32,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            run: 0,
+            played: 0,
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Whether all the inputs were played.
+    pub fn finished(&self) -> bool {
+        self.run >= self.replay.frames.len()
+    }
+
+    fn next_frame(&mut self) -> Option<ReplayFrame> {
+        let &(count, frame) = self.replay.frames.get(self.run)?;
+        self.played += 1;
+        if self.played >= count {
+            self.run += 1;
+            self.played = 0;
+        }
+        Some(frame)
+    }
+}
+
+/// Steps where gameplay moves on, pausing in between doesn't change the run.
+fn is_recorded(state: Res<State<GameState>>) -> bool {
+    matches!(state.0, GameState::Playing | GameState::Drafting)
+}
+
+fn start_playback(
+    playback: Res<ReplayPlayback>,
+    mut next_seed: ResMut<NextRunSeed>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    next_seed.0 = Some(playback.replay.seed);
+    next_state.set(GameState::Playing);
+}
+
+fn stop_playback(mut commands: Commands) {
+    commands.remove_resource::<ReplayPlayback>();
+}
+
+fn start_recording(mut recording: ResMut<RunRecording>, run_seed: Res<RunSeed>) {
+    recording.0 = Replay::new(*run_seed);
+}
+
+/// Runs after the state transition and before the gameplay systems, so the inputs line up with
+/// the state they were recorded in.
+fn play_back_inputs(
+    mut playback: ResMut<ReplayPlayback>,
+    mut players: Query<&mut ActionState<PlayerActions>, With<Player>>,
+    mut mouse_position: ResMut<MousePosition>,
+    mut draft_choices: EventWriter<DraftChoice>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if playback.finished() {
+        next_state.set(GameState::MainMenu);
+        return;
+    }
+    let Some(frame) = playback.next_frame() else { return; };
+
+    for mut action_state in &mut players {
+        frame.apply(&mut action_state);
+    }
+    **mouse_position = frame.mouse_position;
+    if let Some(choice) = frame.draft_choice {
+        draft_choices.send(choice);
+    }
+}
+
+fn record_inputs(
+    mut recording: ResMut<RunRecording>,
+    players: Query<&ActionState<PlayerActions>, With<Player>>,
+    mouse_position: Res<MousePosition>,
+    mut draft_choices: EventReader<DraftChoice>,
+) {
+    // The player is gone on the step it dies, which still has to be played back
+    let action_state = players.get_single().cloned().unwrap_or_default();
+    let draft_choice = draft_choices.iter().last().copied();
+
+    recording.0.push(ReplayFrame::record(
+        &action_state,
+        &mouse_position,
+        draft_choice,
+    ));
+}
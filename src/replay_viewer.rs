@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::GameAssets,
+    replay::{Replay, ReplayPlayback, RunRecording},
+    state::{OnEnterRun, OnExitRun},
+    timestep::StepRate,
+};
+
+/// Saves the replay of every run played in the client, and shows replays being watched with a
+/// key to fast-forward them.
+pub struct ReplayViewerPlugin;
+
+impl Plugin for ReplayViewerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_label.in_schedule(OnEnterRun))
+            .add_system(fast_forward.run_if(resource_exists::<ReplayPlayback>()))
+            .add_systems((save_last_run, reset_speed, despawn_label).in_schedule(OnExitRun));
+    }
+}
+
+/// Speeds cycled through with [`FAST_FORWARD_KEY`]
+const SPEEDS: [u32; 4] = [1, 2, 4, 8];
+const FAST_FORWARD_KEY: KeyCode = KeyCode::F;
+
+#[derive(Component, Default, Debug)]
+struct ReplayLabel;
+
+fn label(speed: u32) -> String {
+    format!("Replay {speed}x (F to fast-forward)")
+}
+
+fn spawn_label(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if playback.is_none() {
+        return;
+    }
+
+    commands.spawn((
+        ReplayLabel,
+        TextBundle::from_section(
+            label(1),
+            TextStyle {
+                font: assets.font_normal.clone(),
+                font_size: 32.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect::new(Val::Auto, Val::Px(20.0), Val::Px(20.0), Val::Auto),
+            ..Default::default()
+        }),
+    ));
+}
+
+fn fast_forward(
+    keys: Res<Input<KeyCode>>,
+    mut step_rate: ResMut<StepRate>,
+    mut labels: Query<&mut Text, With<ReplayLabel>>,
+) {
+    if !keys.just_pressed(FAST_FORWARD_KEY) {
+        return;
+    }
+
+    let speed = match *step_rate {
+        StepRate::FastForward(speed) => speed,
+        _ => 1,
+    };
+    let index = SPEEDS.iter().position(|&other| other == speed).unwrap_or(0);
+    let speed = SPEEDS[(index + 1) % SPEEDS.len()];
+
+    *step_rate = if speed == 1 {
+        StepRate::RealTime
+    } else {
+        StepRate::FastForward(speed)
+    };
+    for mut text in &mut labels {
+        text.sections[0].value = label(speed);
+    }
+}
+
+/// Watching a replay would only save a copy of it, or a shortened one when quit early.
+fn save_last_run(recording: Res<RunRecording>, playback: Option<Res<ReplayPlayback>>) {
+    if playback.is_some() {
+        return;
+    }
+    let Some(path) = Replay::last_run_path() else { return; };
+
+    recording.0.save(&path);
+    info!("Saved the replay of the run to {}", path.display());
+}
+
+fn reset_speed(mut step_rate: ResMut<StepRate>) {
+    if matches!(*step_rate, StepRate::FastForward(_)) {
+        *step_rate = StepRate::RealTime;
+    }
+}
+
+fn despawn_label(mut commands: Commands, labels: Query<Entity, With<ReplayLabel>>) {
+    for e in &labels {
+        commands.entity(e).despawn_recursive();
+    }
+}
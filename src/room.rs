@@ -1,12 +1,14 @@
-use std::iter::repeat_with;
-
 use bevy::prelude::*;
-use bevy_rapier2d::prelude::{Collider, RigidBody};
-use bevy_turborand::{DelegatedRng, GlobalRng};
+use bevy_turborand::{DelegatedRng, RngComponent};
 
 use crate::{
-    enemy::{Enemy, SpawnEnemyEvent},
-    state::GameState,
+    arena::{spawn_arena, Arena, ArenaLayout},
+    assets::GameAssets,
+    enemy::{Enemy, EnemySet, SpawnEnemyEvent},
+    player::Player,
+    seed::SpawnPositionRng,
+    state::{GameState, OnExitRun},
+    timestep::AddToStep,
 };
 
 pub struct RoomPlugin;
@@ -16,16 +18,17 @@ impl Plugin for RoomPlugin {
         app.register_type::<Room>()
             .register_type::<SpawnRoomEvent>()
             .register_type::<RoomClearedEvent>()
-            .add_event::<SpawnRoomEvent>()
-            .add_event::<RoomClearedEvent>()
-            .add_systems(
+            .add_step_event::<SpawnRoomEvent>()
+            .add_step_event::<RoomClearedEvent>()
+            .configure_step_set(RoomSet::ClearedCheck.after(EnemySet::Die))
+            .add_step_systems(
                 (
                     check_room_cleared.in_set(RoomSet::ClearedCheck),
                     spawn_rooms.in_set(RoomSet::Spawn),
                 )
                     .in_set(OnUpdate(GameState::Playing)),
             )
-            .add_system(cleanup_room.in_schedule(OnExit(GameState::Playing)));
+            .add_system(cleanup_room.in_schedule(OnExitRun));
     }
 }
 
@@ -38,6 +41,7 @@ pub enum RoomSet {
 #[derive(Clone, Default, Debug, Reflect, FromReflect)]
 pub struct Room {
     pub enemies: Vec<Enemy>,
+    pub layout: Handle<ArenaLayout>,
 }
 
 #[derive(Clone, Default, Debug, Reflect, FromReflect)]
@@ -45,79 +49,43 @@ pub struct SpawnRoomEvent {
     pub room: Room,
 }
 
-const FLOOR_COLOR: Color = Color::DARK_GRAY;
-
-#[derive(Component, Clone, Default, Debug)]
-struct Arena;
-
 fn spawn_rooms(
     mut commands: Commands,
     mut room_spawn_events: EventReader<SpawnRoomEvent>,
     mut spawn_enemy_events: EventWriter<SpawnEnemyEvent>,
-    mut rand: ResMut<GlobalRng>,
-    arenas: Query<(), With<Arena>>,
+    mut rngs: Query<&mut RngComponent, With<SpawnPositionRng>>,
+    arenas: Query<(Entity, &Arena)>,
+    mut players: Query<&mut Transform, With<Player>>,
+    layouts: Res<Assets<ArenaLayout>>,
+    assets: Res<GameAssets>,
 ) {
+    let Ok(mut rand) = rngs.get_single_mut() else { return; };
     for SpawnRoomEvent { room } in room_spawn_events.iter() {
-        // Spawn static elements
-        if arenas.iter().len() == 0 {
-            commands.spawn((
-                Arena,
-                RigidBody::Fixed,
-                Collider::compound(vec![
-                    (
-                        Vec2::new(0.0, 8.0 * 16.0),
-                        0.0,
-                        Collider::cuboid(29.0 * 8.0, 1.0),
-                    ),
-                    (
-                        Vec2::new(0.0, -8.0 * 16.0),
-                        0.0,
-                        Collider::cuboid(29.0 * 8.0, 1.0),
-                    ),
-                    (
-                        Vec2::new(14.5 * 16.0, 0.0),
-                        0.0,
-                        Collider::cuboid(1.0, 16.0 * 8.0),
-                    ),
-                    (
-                        Vec2::new(-14.5 * 16.0, 0.0),
-                        0.0,
-                        Collider::cuboid(1.0, 16.0 * 8.0),
-                    ),
-                ]),
-                SpriteBundle {
-                    sprite: Sprite {
-                        color: FLOOR_COLOR,
-                        custom_size: Some(Vec2::new(29.0 * 16.0, 16.0 * 16.0)),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-            ));
+        let Some(layout) = layouts.get(&room.layout) else {
+            warn!("Room spawned without an arena layout");
+            continue;
+        };
+
+        // Spawn static elements, rooms in a row with the same layout keep the arena
+        if !arenas.iter().any(|(_, arena)| arena.layout == room.layout) {
+            for (arena, _) in &arenas {
+                commands.entity(arena).despawn_recursive();
+            }
+            spawn_arena(
+                &mut commands,
+                room.layout.clone(),
+                layout,
+                assets.tiles.clone(),
+            );
+            // The player could be standing where the new layout has a wall
+            for mut transform in &mut players {
+                transform.translation = Vec2::ZERO.extend(transform.translation.z);
+            }
         }
 
-        const SPAWN_AREA_SIZE: f32 = 64.0;
-        const SPAWN_AREA_OFFSET: Vec2 =
-            Vec2::new(14.0 * 16.0 - SPAWN_AREA_SIZE, 7.0 * 16.0 - SPAWN_AREA_SIZE);
-
-        let random_positions = repeat_with(|| (rand.f32_normalized(), rand.f32_normalized()))
-            .map(|(x, y)| {
-                (
-                    if x < 0.0 {
-                        x * SPAWN_AREA_SIZE - SPAWN_AREA_OFFSET.x
-                    } else {
-                        x * SPAWN_AREA_SIZE + SPAWN_AREA_OFFSET.x
-                    },
-                    if y < 0.0 {
-                        y * SPAWN_AREA_SIZE - SPAWN_AREA_OFFSET.y
-                    } else {
-                        y * SPAWN_AREA_SIZE + SPAWN_AREA_OFFSET.y
-                    },
-                )
-            })
-            .map(|(x, y)| Vec2::new(x, y));
-
-        for (&enemy, translation) in room.enemies.iter().zip(random_positions) {
+        let mut spawn_points = layout.spawn_points();
+        rand.shuffle(&mut spawn_points);
+        for (&enemy, &translation) in room.enemies.iter().zip(spawn_points.iter().cycle()) {
             spawn_enemy_events.send(SpawnEnemyEvent { enemy, translation })
         }
     }
@@ -130,12 +98,17 @@ fn check_room_cleared(
     rooms: Query<(), With<Arena>>,
     enemies: Query<(), With<Enemy>>,
     mut cleared: Local<bool>,
+    mut populated: Local<bool>,
     mut room_clear_events: EventWriter<RoomClearedEvent>,
     mut room_spawn_events: EventReader<SpawnRoomEvent>,
 ) {
+    let enemy_count = enemies.iter().len();
+    // The arena is spawned before the enemies of the room, so wait for them to show up
+    if enemy_count > 0 {
+        *populated = true;
+    }
     for _ in &rooms {
-        let enemy_count = enemies.iter().len();
-        if enemy_count == 0 && !*cleared {
+        if enemy_count == 0 && *populated && !*cleared {
             room_clear_events.send_default();
             *cleared = true;
         }
@@ -143,6 +116,7 @@ fn check_room_cleared(
 
     for _ in room_spawn_events.iter() {
         *cleared = false;
+        *populated = false;
     }
 }
 
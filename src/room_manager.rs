@@ -1,12 +1,15 @@
-use bevy::prelude::*;
-use bevy_turborand::{DelegatedRng, GlobalRng};
+use bevy::{prelude::*, time::Stopwatch};
+use bevy_turborand::{DelegatedRng, RngComponent};
 
 use crate::{
+    assets::GameAssets,
     enemy::Enemy,
-    health::{Health, MaxHealth},
+    health::{HealEvent, MaxHealth},
     player::Player,
     room::{Room, RoomClearedEvent, RoomSet, SpawnRoomEvent},
-    state::GameState,
+    seed::RoomRng,
+    state::{GameState, OnEnterRun},
+    timestep::AddToStep,
 };
 
 pub struct RoomManagerPlugin;
@@ -14,66 +17,119 @@ pub struct RoomManagerPlugin;
 impl Plugin for RoomManagerPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<CurrentRoom>()
-            .add_system(init.in_schedule(OnEnter(GameState::Playing)))
-            .add_systems(
+            .init_resource::<RoomDifficulty>()
+            .add_system(init.in_schedule(OnEnterRun))
+            .add_step_systems(
                 (room_loop, heal_player)
                     .in_set(OnUpdate(GameState::Playing))
                     .after(RoomSet::ClearedCheck)
                     .before(RoomSet::Spawn),
-            );
+            )
+            .add_step_system(tick_run_time.in_set(OnUpdate(GameState::Playing)))
+            .add_system(spawn_next_room.in_schedule(OnExit(GameState::Drafting)));
     }
 }
 
-fn init(mut commands: Commands, mut events: EventWriter<SpawnRoomEvent>) {
+fn init(mut commands: Commands, mut events: EventWriter<SpawnRoomEvent>, assets: Res<GameAssets>) {
     events.send(SpawnRoomEvent {
         room: Room {
             enemies: vec![Enemy::Bomb, Enemy::Bomb],
+            layout: assets.arenas.first().cloned().unwrap_or_default(),
         },
     });
 
     commands.insert_resource(CurrentRoom::default());
+    commands.insert_resource(RunTime::default());
 }
 
 #[derive(Resource, Clone, Default, Debug, Reflect, FromReflect)]
 #[reflect(Resource, Debug)]
-pub struct CurrentRoom(u32);
+pub struct CurrentRoom(pub u32);
+
+/// Time spent playing the current run, excluding drafting.
+#[derive(Resource, Clone, Default, Debug, Deref, DerefMut)]
+pub struct RunTime(pub Stopwatch);
+
+fn tick_run_time(mut run_time: ResMut<RunTime>, time: Res<Time>) {
+    run_time.tick(time.delta());
+}
+
+/// How many enemies the rooms after the first get. Each room has a budget of
+/// `base + per_room * current_room`, give or take up to `spread`, spent on random enemies until
+/// it runs out.
+#[derive(Resource, Clone, Debug)]
+pub struct RoomDifficulty {
+    pub base: f32,
+    pub per_room: f32,
+    pub spread: f32,
+    /// The enemies to pick from and how much of the budget each costs
+    pub enemy_costs: Vec<(Enemy, f32)>,
+}
+
+impl Default for RoomDifficulty {
+    fn default() -> Self {
+        Self {
+            base: 3.0,
+            per_room: 1.0,
+            spread: 2.0,
+            enemy_costs: vec![(Enemy::Bomb, 1.0), (Enemy::Cat, 2.0)],
+        }
+    }
+}
 
 fn room_loop(
-    mut events: EventWriter<SpawnRoomEvent>,
     mut room_cleared_events: EventReader<RoomClearedEvent>,
     mut current_room: ResMut<CurrentRoom>,
-    mut rng: ResMut<GlobalRng>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
     for _ in room_cleared_events.iter() {
         current_room.0 += 1;
+        next_state.set(GameState::Drafting);
+    }
+}
 
-        let enemy_options = [(Enemy::Bomb, 1.0), (Enemy::Cat, 2.0)];
-
-        let mut room_difficulty = current_room.0 as f32 + 3.0 + rng.f32_normalized() * 2.0;
+fn spawn_next_room(
+    mut events: EventWriter<SpawnRoomEvent>,
+    current_room: Res<CurrentRoom>,
+    difficulty: Res<RoomDifficulty>,
+    assets: Res<GameAssets>,
+    mut rngs: Query<&mut RngComponent, With<RoomRng>>,
+) {
+    let Ok(mut rng) = rngs.get_single_mut() else { return; };
 
-        info!(
-            "Switched to room {}, difficulty = {}",
-            current_room.0, room_difficulty
-        );
-        let mut enemies = Vec::new();
-        while room_difficulty > 0.0 {
-            let option = rng.sample(&enemy_options).unwrap();
-            enemies.push(option.0);
-            room_difficulty -= option.1;
-        }
+    let mut room_difficulty = difficulty.base
+        + difficulty.per_room * current_room.0 as f32
+        + rng.f32_normalized() * difficulty.spread;
 
-        events.send(SpawnRoomEvent {
-            room: Room { enemies },
-        });
+    info!(
+        "Switched to room {}, difficulty = {}",
+        current_room.0, room_difficulty
+    );
+    let mut enemies = Vec::new();
+    while room_difficulty > 0.0 {
+        let Some(&(enemy, cost)) = rng.sample(&difficulty.enemy_costs) else { break; };
+        enemies.push(enemy);
+        // Free enemies would never use up the budget
+        room_difficulty -= cost.max(0.1);
     }
+
+    let layout = rng.sample(&assets.arenas).cloned().unwrap_or_default();
+
+    events.send(SpawnRoomEvent {
+        room: Room { enemies, layout },
+    });
 }
 
 fn heal_player(
-    mut player: Query<(&mut Health, &MaxHealth), With<Player>>,
+    player: Query<(Entity, &MaxHealth), With<Player>>,
     mut room_cleared_events: EventReader<RoomClearedEvent>,
+    mut heal_events: EventWriter<HealEvent>,
 ) {
     for _ in room_cleared_events.iter() {
-        let (mut health, max_health) = player.single_mut();
-        **health = **max_health;
+        let Ok((player_id, max_health)) = player.get_single() else { return; };
+        heal_events.send(HealEvent {
+            healed_id: player_id,
+            amount: **max_health,
+        });
     }
 }
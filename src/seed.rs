@@ -0,0 +1,112 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use bevy_turborand::{DelegatedRng, GlobalRng, RngComponent};
+use serde::{Deserialize, Serialize};
+
+use crate::state::{OnEnterRun, OnExitRun};
+
+pub struct SeedPlugin;
+
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<RunSeed>()
+            .init_resource::<RunSeed>()
+            .init_resource::<NextRunSeed>()
+            .add_system(spawn_run_rngs.in_schedule(OnEnterRun).in_set(SeedSet))
+            .add_system(despawn_run_rngs.in_schedule(OnExitRun));
+    }
+}
+
+/// Picks the [`RunSeed`] when a run starts, in [`OnEnterRun`].
+#[derive(SystemSet, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct SeedSet;
+
+/// Seed of the current or last run. Every random choice that affects gameplay comes from it, so
+/// the same seed and inputs play out the same run.
+#[derive(
+    Resource,
+    Clone,
+    Copy,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Reflect,
+    FromReflect,
+    Serialize,
+    Deserialize,
+)]
+#[reflect(Resource, Default, Debug)]
+pub struct RunSeed(pub u64);
+
+impl RunSeed {
+    /// Most hex digits a seed can have.
+    pub const MAX_DIGITS: usize = 16;
+
+    /// Parses the hex digits shown by [`fmt::Display`].
+    pub fn parse(digits: &str) -> Option<Self> {
+        if digits.is_empty() || digits.len() > Self::MAX_DIGITS {
+            return None;
+        }
+        u64::from_str_radix(digits, 16).ok().map(Self)
+    }
+}
+
+impl fmt::Display for RunSeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016X}", self.0)
+    }
+}
+
+/// Seed for the next run to start, a random one is picked if there is none.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct NextRunSeed(pub Option<RunSeed>);
+
+/// Holds the [`RngComponent`] the room manager picks the enemies of each room with.
+#[derive(Component, Default, Debug)]
+pub struct RoomRng;
+
+/// Holds the [`RngComponent`] enemy spawn positions are picked with.
+#[derive(Component, Default, Debug)]
+pub struct SpawnPositionRng;
+
+/// Holds the [`RngComponent`] draft offers are picked with.
+#[derive(Component, Default, Debug)]
+pub struct DraftRng;
+
+/// Holds the [`RngComponent`] upgrade drops are rolled with.
+#[derive(Component, Default, Debug)]
+pub struct UpgradeRng;
+
+/// Marks the entities holding the random number generators of a run.
+#[derive(Component, Default, Debug)]
+struct RunRng;
+
+/// Each system gets its own generator forked from the seed, so how many numbers one of them
+/// draws doesn't change what the others get.
+fn spawn_run_rngs(
+    mut commands: Commands,
+    mut next_seed: ResMut<NextRunSeed>,
+    mut run_seed: ResMut<RunSeed>,
+    mut global_rng: ResMut<GlobalRng>,
+) {
+    *run_seed = next_seed
+        .0
+        .take()
+        .unwrap_or_else(|| RunSeed(global_rng.u64(..)));
+    info!("Starting run with seed {}", *run_seed);
+
+    let mut root = RngComponent::with_seed(run_seed.0);
+    commands.spawn((RunRng, RoomRng, RngComponent::from(&mut root)));
+    commands.spawn((RunRng, SpawnPositionRng, RngComponent::from(&mut root)));
+    commands.spawn((RunRng, DraftRng, RngComponent::from(&mut root)));
+    commands.spawn((RunRng, UpgradeRng, RngComponent::from(&mut root)));
+}
+
+fn despawn_run_rngs(mut commands: Commands, rngs: Query<Entity, With<RunRng>>) {
+    for e in &rngs {
+        commands.entity(e).despawn();
+    }
+}
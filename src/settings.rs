@@ -0,0 +1,157 @@
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::bindings::Bindings;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Settings>()
+            .init_resource::<Settings>()
+            .add_system(apply_window_settings)
+            .add_system(save_settings.in_base_set(CoreSet::Last));
+    }
+}
+
+pub const MAX_SHAKE_INTENSITY: f32 = 2.0;
+/// Seconds without changes before the settings are saved, so stepping through a slider writes
+/// the file once.
+const SAVE_DELAY: f32 = 0.5;
+
+/// Player preferences, loaded from and saved to [`Settings::path`].
+#[derive(Resource, Clone, Debug, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Resource, Default, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// Volumes are between 0 and 1, sound effects are multiplied by the master volume
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub window_mode: WindowMode,
+    pub present_mode: PresentMode,
+    /// Multiplier for camera shake, between 0 and [`MAX_SHAKE_INTENSITY`]
+    pub shake_intensity: f32,
+    pub damage_numbers: bool,
+    #[reflect(ignore)]
+    pub bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            #[cfg(not(target_family = "wasm"))]
+            window_mode: WindowMode::BorderlessFullscreen,
+            #[cfg(target_family = "wasm")]
+            window_mode: WindowMode::Windowed,
+            present_mode: PresentMode::AutoNoVsync,
+            shake_intensity: 1.0,
+            damage_numbers: true,
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, falling back to the defaults if it's missing or invalid.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else { return Self::default(); };
+        let Ok(contents) = std::fs::read_to_string(&path) else { return Self::default(); };
+
+        match ron::from_str::<Settings>(&contents) {
+            Ok(settings) => settings.validated(),
+            Err(error) => {
+                warn!("Invalid settings in {}: {error}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::path() else { return; };
+        let contents = match ron::ser::to_string_pretty(self, Default::default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!("Couldn't serialize settings: {error}");
+                return;
+            }
+        };
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, contents));
+        if let Err(error) = result {
+            warn!("Couldn't save settings to {}: {error}", path.display());
+        }
+    }
+
+    /// Location of the settings file, there is none on the web.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn path() -> Option<std::path::PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("labrats").join("settings.ron"))
+    }
+
+    #[cfg(target_family = "wasm")]
+    pub fn path() -> Option<std::path::PathBuf> {
+        None
+    }
+
+    /// Keeps values edited by hand within their ranges, and fills in bindings for actions added
+    /// since the file was saved.
+    pub fn validated(self) -> Self {
+        Self {
+            bindings: self.bindings.with_missing_defaults(),
+            master_volume: self.master_volume.clamp(0.0, 1.0),
+            sfx_volume: self.sfx_volume.clamp(0.0, 1.0),
+            shake_intensity: self.shake_intensity.clamp(0.0, MAX_SHAKE_INTENSITY),
+            ..self
+        }
+    }
+
+    pub fn effective_sfx_volume(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+}
+
+/// The window starts out with the loaded settings, so this only applies changes made afterwards.
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    for mut window in &mut windows {
+        if window.mode != settings.window_mode {
+            window.mode = settings.window_mode;
+        }
+        if window.present_mode != settings.present_mode {
+            window.present_mode = settings.present_mode;
+        }
+    }
+}
+
+fn save_settings(
+    settings: Res<Settings>,
+    mut exit_events: EventReader<AppExit>,
+    time: Res<Time>,
+    mut pending: Local<Option<Timer>>,
+) {
+    if settings.is_changed() && !settings.is_added() {
+        *pending = Some(Timer::from_seconds(SAVE_DELAY, TimerMode::Once));
+    }
+    let exiting = exit_events.iter().count() > 0;
+    let Some(timer) = pending.as_mut() else { return; };
+
+    // Saved right away when quitting, there is no next frame to wait for
+    if timer.tick(time.delta()).finished() || exiting {
+        settings.save();
+        *pending = None;
+    }
+}
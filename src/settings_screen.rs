@@ -0,0 +1,365 @@
+use bevy::{
+    prelude::*,
+    ui::FocusPolicy,
+    window::{PresentMode, WindowMode},
+};
+use leafwing_input_manager::{
+    prelude::{ActionState, ActionStateDriver, InputManagerPlugin, InputMap},
+    Actionlike, InputManagerBundle,
+};
+
+use crate::{
+    assets::GameAssets,
+    settings::{Settings, MAX_SHAKE_INTENSITY},
+};
+
+pub struct SettingsScreenPlugin;
+
+impl Plugin for SettingsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<SettingsScreenState>()
+            .add_plugin(InputManagerPlugin::<SettingsActions>::default())
+            .add_system(spawn_screen.in_schedule(OnEnter(SettingsScreenState::Open)))
+            .add_systems(
+                (handle_actions, close_on_escape, show_values, highlight)
+                    .in_set(OnUpdate(SettingsScreenState::Open)),
+            )
+            .add_system(despawn_screen.in_schedule(OnExit(SettingsScreenState::Open)));
+    }
+}
+
+/// The settings screen is drawn over the main menu or the pause menu, so it's a separate state
+/// that doesn't end the run.
+#[derive(States, Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum SettingsScreenState {
+    #[default]
+    Closed,
+    Open,
+    /// The key binding screen, opened from the settings screen
+    Controls,
+}
+
+const VOLUME_STEP: f32 = 0.1;
+const SHAKE_STEP: f32 = 0.25;
+const WINDOW_MODES: [WindowMode; 3] = [
+    WindowMode::Windowed,
+    WindowMode::BorderlessFullscreen,
+    WindowMode::Fullscreen,
+];
+
+#[derive(Component, Default, Debug)]
+struct SettingsScreenRoot;
+
+#[derive(Actionlike, Component, PartialEq, Eq, Clone, Copy, Hash, Debug)]
+enum SettingsActions {
+    MasterVolumeDown,
+    MasterVolumeUp,
+    SfxVolumeDown,
+    SfxVolumeUp,
+    PreviousWindowMode,
+    NextWindowMode,
+    ToggleVsync,
+    ShakeDown,
+    ShakeUp,
+    ToggleDamageNumbers,
+    Controls,
+    Back,
+}
+
+/// Text showing the current value of a setting.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+enum SettingValue {
+    MasterVolume,
+    SfxVolume,
+    WindowMode,
+    Vsync,
+    ShakeIntensity,
+    DamageNumbers,
+}
+
+impl SettingValue {
+    fn format(self, settings: &Settings) -> String {
+        let percent = |value: f32| format!("{}%", (value * 100.0).round());
+        match self {
+            SettingValue::MasterVolume => percent(settings.master_volume),
+            SettingValue::SfxVolume => percent(settings.sfx_volume),
+            SettingValue::WindowMode => match settings.window_mode {
+                WindowMode::Windowed => "Windowed",
+                WindowMode::BorderlessFullscreen => "Borderless",
+                WindowMode::SizedFullscreen | WindowMode::Fullscreen => "Fullscreen",
+            }
+            .to_string(),
+            SettingValue::Vsync => on_off(settings.present_mode != PresentMode::AutoNoVsync),
+            SettingValue::ShakeIntensity => percent(settings.shake_intensity),
+            SettingValue::DamageNumbers => on_off(settings.damage_numbers),
+        }
+    }
+}
+
+fn on_off(value: bool) -> String {
+    if value { "On" } else { "Off" }.to_string()
+}
+
+fn spawn_screen(mut commands: Commands, assets: Res<GameAssets>, settings: Res<Settings>) {
+    let text_style = TextStyle {
+        font: assets.font_normal.clone(),
+        font_size: 40.0,
+        color: Color::WHITE,
+    };
+
+    // Covers whichever menu it was opened from, and keeps its buttons from being clicked
+    commands
+        .spawn((
+            SettingsScreenRoot,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    gap: Size::new(Val::Auto, Val::Px(12.0)),
+                    ..Default::default()
+                },
+                background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(2),
+                ..Default::default()
+            },
+            InputManagerBundle::<SettingsActions> {
+                input_map: InputMap::default(),
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Settings",
+                TextStyle {
+                    font: assets.font_italic.clone(),
+                    font_size: 64.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            let root = parent.parent_entity();
+            for (label, value, decrease, increase) in [
+                (
+                    "Master volume",
+                    SettingValue::MasterVolume,
+                    SettingsActions::MasterVolumeDown,
+                    SettingsActions::MasterVolumeUp,
+                ),
+                (
+                    "Sound volume",
+                    SettingValue::SfxVolume,
+                    SettingsActions::SfxVolumeDown,
+                    SettingsActions::SfxVolumeUp,
+                ),
+                (
+                    "Window",
+                    SettingValue::WindowMode,
+                    SettingsActions::PreviousWindowMode,
+                    SettingsActions::NextWindowMode,
+                ),
+                (
+                    "VSync",
+                    SettingValue::Vsync,
+                    SettingsActions::ToggleVsync,
+                    SettingsActions::ToggleVsync,
+                ),
+                (
+                    "Screen shake",
+                    SettingValue::ShakeIntensity,
+                    SettingsActions::ShakeDown,
+                    SettingsActions::ShakeUp,
+                ),
+                (
+                    "Damage numbers",
+                    SettingValue::DamageNumbers,
+                    SettingsActions::ToggleDamageNumbers,
+                    SettingsActions::ToggleDamageNumbers,
+                ),
+            ] {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            gap: Size::new(Val::Px(16.0), Val::Auto),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(label, text_style.clone()),
+                            style: Style {
+                                size: Size::new(Val::Px(320.0), Val::Auto),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        });
+                        spawn_button(parent, root, decrease, "<", 50.0, &text_style);
+                        parent.spawn((
+                            value,
+                            TextBundle {
+                                text: Text::from_section(
+                                    value.format(&settings),
+                                    text_style.clone(),
+                                )
+                                .with_alignment(TextAlignment::Center),
+                                style: Style {
+                                    size: Size::new(Val::Px(220.0), Val::Auto),
+                                    justify_content: JustifyContent::Center,
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                        ));
+                        spawn_button(parent, root, increase, ">", 50.0, &text_style);
+                    });
+            }
+
+            spawn_button(
+                parent,
+                root,
+                SettingsActions::Controls,
+                "Controls",
+                200.0,
+                &text_style,
+            );
+            spawn_button(
+                parent,
+                root,
+                SettingsActions::Back,
+                "Back",
+                200.0,
+                &text_style,
+            );
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    root: Entity,
+    action: SettingsActions,
+    label: &str,
+    width: f32,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    size: Size::new(Val::Px(width), Val::Px(50.0)),
+                    ..Default::default()
+                },
+                background_color: Color::DARK_GRAY.into(),
+                ..Default::default()
+            },
+            ActionStateDriver {
+                action,
+                entity: root,
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+fn handle_actions(
+    input: Query<&ActionState<SettingsActions>>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<SettingsScreenState>>,
+) {
+    for input in &input {
+        let Some(&action) = input.get_just_pressed().first() else { continue; };
+
+        match action {
+            SettingsActions::MasterVolumeDown => {
+                step(&mut settings.master_volume, -VOLUME_STEP, 1.0)
+            }
+            SettingsActions::MasterVolumeUp => step(&mut settings.master_volume, VOLUME_STEP, 1.0),
+            SettingsActions::SfxVolumeDown => step(&mut settings.sfx_volume, -VOLUME_STEP, 1.0),
+            SettingsActions::SfxVolumeUp => step(&mut settings.sfx_volume, VOLUME_STEP, 1.0),
+            SettingsActions::PreviousWindowMode => {
+                settings.window_mode = cycle_window_mode(settings.window_mode, -1)
+            }
+            SettingsActions::NextWindowMode => {
+                settings.window_mode = cycle_window_mode(settings.window_mode, 1)
+            }
+            SettingsActions::ToggleVsync => {
+                settings.present_mode = if settings.present_mode == PresentMode::AutoNoVsync {
+                    PresentMode::AutoVsync
+                } else {
+                    PresentMode::AutoNoVsync
+                };
+            }
+            SettingsActions::ShakeDown => step(
+                &mut settings.shake_intensity,
+                -SHAKE_STEP,
+                MAX_SHAKE_INTENSITY,
+            ),
+            SettingsActions::ShakeUp => step(
+                &mut settings.shake_intensity,
+                SHAKE_STEP,
+                MAX_SHAKE_INTENSITY,
+            ),
+            SettingsActions::ToggleDamageNumbers => {
+                settings.damage_numbers = !settings.damage_numbers
+            }
+            SettingsActions::Controls => next_state.set(SettingsScreenState::Controls),
+            SettingsActions::Back => next_state.set(SettingsScreenState::Closed),
+        }
+    }
+}
+
+fn step(value: &mut f32, step: f32, max: f32) {
+    *value = (*value + step).clamp(0.0, max);
+}
+
+fn cycle_window_mode(mode: WindowMode, direction: isize) -> WindowMode {
+    let index = WINDOW_MODES
+        .iter()
+        .position(|other| *other == mode)
+        .unwrap_or(0) as isize;
+    let len = WINDOW_MODES.len() as isize;
+    WINDOW_MODES[(index + direction).rem_euclid(len) as usize]
+}
+
+fn close_on_escape(
+    keys: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<SettingsScreenState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(SettingsScreenState::Closed);
+    }
+}
+
+fn show_values(settings: Res<Settings>, mut values: Query<(&SettingValue, &mut Text)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (value, mut text) in &mut values {
+        text.sections[0].value = value.format(&settings);
+    }
+}
+
+fn despawn_screen(mut commands: Commands, screens: Query<Entity, With<SettingsScreenRoot>>) {
+    for e in &screens {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn highlight(
+    mut buttons: Query<(&mut BackgroundColor, &Interaction), (With<Button>, Changed<Interaction>)>,
+) {
+    for (mut color, interaction) in &mut buttons {
+        if let Interaction::Hovered = *interaction {
+            *color = BackgroundColor(Color::GRAY);
+        } else {
+            *color = BackgroundColor(Color::DARK_GRAY);
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 use bevy_turborand::{DelegatedRng, GlobalRng};
 
+use crate::settings::Settings;
+
 pub struct ShakePlugin;
 
 impl Plugin for ShakePlugin {
@@ -21,6 +23,7 @@ fn shake(
     mut object: Query<(&mut Transform, &mut Shake)>,
     mut rng: ResMut<GlobalRng>,
     time: Res<Time>,
+    settings: Res<Settings>,
 ) {
     for (mut transform, mut shake) in &mut object {
         if shake.amount <= 0.0 {
@@ -30,7 +33,8 @@ fn shake(
             continue;
         }
 
-        let offset = (Vec2::new(rng.f32_normalized(), rng.f32_normalized()) * shake.amount.powi(2))
+        let strength = shake.amount.powi(2) * settings.shake_intensity;
+        let offset = (Vec2::new(rng.f32_normalized(), rng.f32_normalized()) * strength)
             .clamp_length_max(shake.max_translation);
 
         transform.translation.x = offset.x;
@@ -40,8 +44,7 @@ fn shake(
             EulerRot::XYZ,
             0.0,
             0.0,
-            (rng.f32_normalized() * shake.amount.powi(2))
-                .clamp(-shake.max_rotation, shake.max_rotation),
+            (rng.f32_normalized() * strength).clamp(-shake.max_rotation, shake.max_rotation),
         );
 
         shake.amount -= 4.0 * time.delta_seconds();
@@ -1,18 +1,104 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::schedule::{ExecutorKind, ScheduleLabel},
+    prelude::*,
+};
+
+use crate::timestep::{AddToStep, StepSet};
 
 #[derive(States, Clone, Copy, Default, Debug, Reflect, FromReflect, PartialEq, Eq, Hash)]
 pub enum GameState {
     #[default]
     Loading,
     Playing,
+    Drafting,
     DeathScreen,
     MainMenu,
+    Paused,
+    /// Passed through for a frame to end the current run and start a new one
+    Restarting,
+}
+
+impl GameState {
+    /// Whether the player and the current room exist in this state.
+    pub fn in_run(&self) -> bool {
+        matches!(self, Self::Playing | Self::Drafting | Self::Paused)
+    }
 }
 
+/// Runs when entering a state that is part of a run from one that isn't. Use this instead of
+/// `OnEnter(GameState::Playing)` for anything that should survive the states in between rooms.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OnEnterRun;
+
+/// Runs when leaving the states that are part of a run, see [`OnEnterRun`].
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OnExitRun;
+
+/// Runs [`OnEnterRun`] and [`OnExitRun`] after the state transition, in
+/// `CoreSet::StateTransitions` and again in `StepSet::First`.
+#[derive(SystemSet, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct RunTransitionSet;
+
+/// Whether [`OnEnterRun`] ran last, shared by the transitions of the main and step schedules.
+#[derive(Resource, Clone, Default, Debug)]
+struct InRun(bool);
+
 pub struct GlobalStatePlugin;
 
 impl Plugin for GlobalStatePlugin {
     fn build(&self, app: &mut App) {
-        app.add_state::<GameState>().register_type::<GameState>();
+        app.add_state::<GameState>()
+            .register_type::<GameState>()
+            .init_resource::<InRun>()
+            .init_schedule(OnEnterRun)
+            .init_schedule(OnExitRun)
+            .add_system(
+                run_transition_schedules
+                    .after(apply_state_transition::<GameState>)
+                    .in_set(RunTransitionSet)
+                    .in_base_set(CoreSet::StateTransitions),
+            )
+            // Gameplay changes state too, and shouldn't have to wait for the next frame
+            .add_step_systems(
+                (
+                    apply_state_transition::<GameState>,
+                    run_transition_schedules.in_set(RunTransitionSet),
+                )
+                    .chain()
+                    .in_base_set(StepSet::First),
+            );
+        for state in GameState::variants() {
+            app.configure_step_set(
+                OnUpdate(state)
+                    .in_base_set(StepSet::Update)
+                    .run_if(in_state(state)),
+            );
+        }
+
+        // Gameplay is spawned and despawned on transitions, in the same order every run
+        let mut schedules = app.world.resource_mut::<Schedules>();
+        let transitions = GameState::variants()
+            .flat_map(|state| [OnEnter(state).dyn_clone(), OnExit(state).dyn_clone()])
+            .chain([OnEnterRun.dyn_clone(), OnExitRun.dyn_clone()]);
+        for label in transitions {
+            if let Some(schedule) = schedules.get_mut(&*label) {
+                schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+            }
+        }
+    }
+}
+
+fn run_transition_schedules(world: &mut World) {
+    let in_run = world.resource::<State<GameState>>().0.in_run();
+    let mut was_in_run = world.resource_mut::<InRun>();
+    if in_run == was_in_run.0 {
+        return;
+    }
+    was_in_run.0 = in_run;
+
+    if in_run {
+        world.run_schedule(OnEnterRun);
+    } else {
+        world.run_schedule(OnExitRun);
     }
 }
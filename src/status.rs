@@ -0,0 +1,281 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{Damping, ExternalForce};
+use serde::Deserialize;
+
+use crate::{
+    abilities::AbilitySet,
+    enemy::EnemySet,
+    explosion::HandleExplosionSet,
+    health::{DamageEvent, DamageType, Health},
+    timestep::AddToStep,
+};
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<StatusKind>()
+            .register_type::<StatusEffect>()
+            .register_type::<StatusEffects>()
+            .register_type::<BaseDamping>()
+            .register_type::<Incapacitated>()
+            .add_step_event::<ApplyStatusEvent>()
+            .configure_step_set(
+                StatusSet
+                    .after(AbilitySet)
+                    .after(HandleExplosionSet)
+                    .after(EnemySet::AI)
+                    .before(EnemySet::Die),
+            )
+            .add_step_systems(
+                (
+                    init_statuses,
+                    apply_system_buffers,
+                    apply_statuses,
+                    tick_statuses,
+                    burn,
+                    recompute_movement,
+                )
+                    .chain()
+                    .in_set(StatusSet),
+            );
+    }
+}
+
+/// Applies [`ApplyStatusEvent`]s and recomputes the movement of affected entities. Runs after
+/// anything that sets an [`ExternalForce`] for movement, so it can cancel it.
+#[derive(SystemSet, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct StatusSet;
+
+/// Damping added while frozen, on top of any slow.
+const FREEZE_DAMPING: f32 = 100.0;
+
+// Deserialized from a lowercase string, since ability params lose enum variant names
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Reflect, FromReflect)]
+#[serde(try_from = "String")]
+pub enum StatusKind {
+    /// Adds `strength` to linear damping
+    #[default]
+    Slow,
+    /// Deals `strength` damage per second
+    Burn,
+    /// Can't move or act
+    Freeze,
+    /// Can't act and is pushed around freely
+    Stun,
+    /// Takes `strength` times more damage on top of the normal amount
+    Vulnerable,
+}
+
+impl TryFrom<String> for StatusKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "slow" => Ok(Self::Slow),
+            "burn" => Ok(Self::Burn),
+            "freeze" => Ok(Self::Freeze),
+            "stun" => Ok(Self::Stun),
+            "vulnerable" => Ok(Self::Vulnerable),
+            _ => Err(format!("unknown status effect \"{value}\"")),
+        }
+    }
+}
+
+/// How a new effect combines with ones of the same kind already on the target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stacking {
+    /// Every application runs separately and their strengths add up
+    Independent,
+    /// Only the strongest application counts, lasting as long as the longest one
+    Strongest,
+}
+
+impl StatusKind {
+    pub fn stacking(&self) -> Stacking {
+        match self {
+            StatusKind::Burn => Stacking::Independent,
+            StatusKind::Slow | StatusKind::Freeze | StatusKind::Stun | StatusKind::Vulnerable => {
+                Stacking::Strongest
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Reflect, FromReflect)]
+#[serde(default)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub strength: f32,
+    /// Seconds left
+    pub duration: f32,
+    /// Credited with the damage dealt by the effect
+    #[serde(skip)]
+    pub source: Option<Entity>,
+}
+
+impl Default for StatusEffect {
+    fn default() -> Self {
+        Self {
+            kind: StatusKind::Slow,
+            strength: 1.0,
+            duration: 1.0,
+            source: None,
+        }
+    }
+}
+
+/// Effects currently active on an entity. Added to anything with [`Damping`] or [`Health`].
+#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn add(&mut self, effect: StatusEffect) {
+        if effect.kind.stacking() == Stacking::Strongest {
+            if let Some(existing) = self.0.iter_mut().find(|e| e.kind == effect.kind) {
+                existing.strength = existing.strength.max(effect.strength);
+                existing.duration = existing.duration.max(effect.duration);
+                existing.source = effect.source.or(existing.source);
+                return;
+            }
+        }
+        self.0.push(effect);
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.0.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Combined strength of every effect of this kind, 0 if there are none.
+    pub fn strength(&self, kind: StatusKind) -> f32 {
+        self.0
+            .iter()
+            .filter(|effect| effect.kind == kind)
+            .map(|effect| effect.strength)
+            .sum()
+    }
+
+    /// Whether the entity is prevented from using abilities and attacking.
+    pub fn incapacitated(&self) -> bool {
+        self.has(StatusKind::Freeze) || self.has(StatusKind::Stun)
+    }
+}
+
+/// The linear damping of an entity without any effects. [`Damping`] is recomputed from it every
+/// frame.
+#[derive(Component, Clone, Debug, Default, Deref, DerefMut, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct BaseDamping(pub f32);
+
+/// On entities that are frozen or stunned. Kept in sync with their [`StatusEffects`].
+#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct Incapacitated;
+
+#[derive(Clone, Debug)]
+pub struct ApplyStatusEvent {
+    pub target: Entity,
+    pub effect: StatusEffect,
+}
+
+fn init_statuses(
+    mut commands: Commands,
+    new_objects: Query<
+        (Entity, Option<&Damping>),
+        (Or<(Added<Damping>, Added<Health>)>, Without<StatusEffects>),
+    >,
+) {
+    for (object_id, damping) in &new_objects {
+        let mut object = commands.entity(object_id);
+        object.insert(StatusEffects::default());
+        if let Some(damping) = damping {
+            object.insert(BaseDamping(damping.linear_damping));
+        }
+    }
+}
+
+fn apply_statuses(
+    mut status_events: EventReader<ApplyStatusEvent>,
+    mut objects: Query<&mut StatusEffects>,
+) {
+    for status in status_events.iter() {
+        let Ok(mut effects) = objects.get_mut(status.target) else { continue; };
+        effects.add(status.effect.clone());
+    }
+}
+
+fn tick_statuses(mut objects: Query<&mut StatusEffects>, time: Res<Time>) {
+    for mut effects in &mut objects {
+        if effects.0.is_empty() {
+            continue;
+        }
+        for effect in &mut effects.0 {
+            effect.duration -= time.delta_seconds();
+        }
+        effects.0.retain(|effect| effect.duration > 0.0);
+    }
+}
+
+fn burn(
+    objects: Query<(Entity, &StatusEffects)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    time: Res<Time>,
+) {
+    for (object_id, effects) in &objects {
+        for effect in &effects.0 {
+            if effect.kind != StatusKind::Burn {
+                continue;
+            }
+            damage_events.send(DamageEvent {
+                damaged_id: object_id,
+                damage: effect.strength * time.delta_seconds(),
+                source: effect.source,
+                damage_type: DamageType::Fire,
+                hit: false,
+            });
+        }
+    }
+}
+
+fn recompute_movement(
+    mut commands: Commands,
+    mut objects: Query<(
+        Entity,
+        &StatusEffects,
+        Option<(&BaseDamping, &mut Damping)>,
+        Option<&mut ExternalForce>,
+        Option<&Incapacitated>,
+    )>,
+) {
+    for (object_id, effects, damping, force, incapacitated) in &mut objects {
+        let frozen = effects.has(StatusKind::Freeze);
+
+        if let Some((base, mut damping)) = damping {
+            let mut linear_damping = **base + effects.strength(StatusKind::Slow);
+            if frozen {
+                linear_damping += FREEZE_DAMPING;
+            }
+            // Avoid triggering change detection every frame
+            if damping.linear_damping != linear_damping {
+                damping.linear_damping = linear_damping;
+            }
+        }
+
+        if let Some(mut force) = force {
+            if effects.incapacitated() {
+                force.force = Vec2::ZERO;
+            }
+        }
+
+        match (effects.incapacitated(), incapacitated.is_some()) {
+            (true, false) => {
+                commands.entity(object_id).insert(Incapacitated);
+            }
+            (false, true) => {
+                commands.entity(object_id).remove::<Incapacitated>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    ecs::{component::Component, event::Event},
+    prelude::*,
+};
+use bevy_turborand::RngPlugin;
+
+use crate::{
+    abilities::{cooldown::AbilityCooldownTime, heat::Heat, Loadout, UseAbilityEvent},
+    draft::DraftChoice,
+    enemy::{Enemy, SpawnEnemyEvent},
+    headless::HeadlessPlugins,
+    health::Health,
+    mouse_position::MousePosition,
+    player::Player,
+    replay::{Replay, ReplayPlayback, RunRecording},
+    seed::{NextRunSeed, RunSeed},
+    state::GameState,
+    timestep::TIMESTEP,
+    LabratsCorePlugins,
+};
+
+/// How long to wait for the ability definitions to load before giving up.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs [`LabratsCorePlugins`] headless with a fixed RNG seed, one [`TIMESTEP`] per step, for
+/// driving the game from tests.
+pub struct TestHarness {
+    pub app: App,
+}
+
+impl Default for TestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestHarness {
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// Builds the app and waits in the main menu until the assets are loaded.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(HeadlessPlugins)
+            .add_plugins(
+                LabratsCorePlugins
+                    .build()
+                    .set(RngPlugin::new().with_rng_seed(seed)),
+            );
+
+        let mut harness = Self { app };
+        // Assets load on other threads, so this is the one wait that takes real time
+        let started = Instant::now();
+        while harness.state() != GameState::MainMenu {
+            assert!(
+                started.elapsed() < LOAD_TIMEOUT,
+                "assets didn't load within {LOAD_TIMEOUT:?}"
+            );
+            harness.step();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        harness
+    }
+
+    /// Starts a run from the main menu, and steps until the first room is spawned.
+    pub fn start_run(&mut self) -> &mut Self {
+        self.set_state(GameState::Playing);
+        // The abilities get their components once their definitions are applied
+        let ready = self.run_until(1.0, |harness| {
+            let abilities_ready = harness.try_player().is_some_and(|player| {
+                let loadout = harness.app.world.get::<Loadout>(player).unwrap();
+                loadout
+                    .abilities
+                    .iter()
+                    .all(|&ability| harness.get::<AbilityCooldownTime>(ability).is_some())
+            });
+            abilities_ready && !harness.enemies().is_empty()
+        });
+        assert!(ready, "the run didn't start");
+        self
+    }
+
+    /// Starts a run with the given [`RunSeed`], see [`Self::start_run`].
+    pub fn start_run_with_seed(&mut self, seed: u64) -> &mut Self {
+        self.app.insert_resource(NextRunSeed(Some(RunSeed(seed))));
+        self.start_run()
+    }
+
+    /// Starts watching a replay from the main menu, and steps until all of it was played.
+    pub fn watch_replay(&mut self, replay: Replay) -> &mut Self {
+        let steps = replay.len();
+        self.app.insert_resource(ReplayPlayback::new(replay));
+        // One step to leave the main menu, and one to enter the run
+        self.step_frames(2);
+        while self
+            .app
+            .world
+            .get_resource::<ReplayPlayback>()
+            .is_some_and(|playback| !playback.finished())
+        {
+            self.step();
+        }
+        assert_eq!(self.recording().len(), steps, "the replay wasn't played");
+        self
+    }
+
+    /// The replay of the current or last run. Only inputs are recorded, so runs that should
+    /// replay the same have to be played with [`Self::press_key`] and the like rather than
+    /// by sending events.
+    pub fn recording(&self) -> Replay {
+        self.app.world.resource::<RunRecording>().0.clone()
+    }
+
+    /// Starts a run with only the given enemies in the room, instead of the random ones.
+    pub fn start_run_with_enemies(&mut self, enemies: &[(Enemy, Vec2)]) -> Vec<Entity> {
+        self.start_run();
+        let initial = self.enemies();
+        // Spawn the new enemies first, so the room doesn't count as cleared in between
+        let spawned: Vec<_> = enemies
+            .iter()
+            .map(|&(enemy, position)| self.spawn_enemy(enemy, position))
+            .collect();
+        for enemy in initial {
+            self.app.world.entity_mut(enemy).despawn_recursive();
+        }
+        spawned
+    }
+
+    pub fn step(&mut self) -> &mut Self {
+        self.app.update();
+        self
+    }
+
+    pub fn step_frames(&mut self, frames: u32) -> &mut Self {
+        for _ in 0..frames {
+            self.step();
+        }
+        self
+    }
+
+    pub fn step_seconds(&mut self, seconds: f32) -> &mut Self {
+        let frames = (seconds / TIMESTEP.as_secs_f32()).ceil() as u32;
+        self.step_frames(frames)
+    }
+
+    /// Steps until `condition` holds, for at most `max_seconds` of game time. Returns whether it
+    /// held.
+    pub fn run_until(
+        &mut self,
+        max_seconds: f32,
+        mut condition: impl FnMut(&mut Self) -> bool,
+    ) -> bool {
+        let max_frames = (max_seconds / TIMESTEP.as_secs_f32()).ceil() as u32;
+        for _ in 0..max_frames {
+            if condition(self) {
+                return true;
+            }
+            self.step();
+        }
+        condition(self)
+    }
+
+    /// Sends an event, which is handled during the next step.
+    pub fn send<E: Event>(&mut self, event: E) -> &mut Self {
+        self.app.world.send_event(event);
+        self
+    }
+
+    /// Uses the ability in the given slot of the player's loadout during the next step.
+    pub fn use_ability(&mut self, slot: usize) -> &mut Self {
+        let player = self.player();
+        self.send(UseAbilityEvent {
+            loadout: player,
+            ability: slot,
+            strength: 1.0,
+        })
+    }
+
+    /// Spawns an enemy and steps until it exists.
+    pub fn spawn_enemy(&mut self, enemy: Enemy, position: Vec2) -> Entity {
+        let existing = self.enemies();
+        self.send(SpawnEnemyEvent {
+            enemy,
+            translation: position,
+        });
+        self.step();
+        self.enemies()
+            .into_iter()
+            .find(|entity| !existing.contains(entity))
+            .expect("no enemy was spawned")
+    }
+
+    pub fn enemies(&mut self) -> Vec<Entity> {
+        self.app
+            .world
+            .query_filtered::<Entity, With<Enemy>>()
+            .iter(&self.app.world)
+            .collect()
+    }
+
+    /// Holds down a key until [`Self::release_key`].
+    pub fn press_key(&mut self, key: KeyCode) -> &mut Self {
+        self.app.world.resource_mut::<Input<KeyCode>>().press(key);
+        self
+    }
+
+    pub fn release_key(&mut self, key: KeyCode) -> &mut Self {
+        self.app.world.resource_mut::<Input<KeyCode>>().release(key);
+        self
+    }
+
+    pub fn press_mouse(&mut self, button: MouseButton) -> &mut Self {
+        self.app
+            .world
+            .resource_mut::<Input<MouseButton>>()
+            .press(button);
+        self
+    }
+
+    pub fn release_mouse(&mut self, button: MouseButton) -> &mut Self {
+        self.app
+            .world
+            .resource_mut::<Input<MouseButton>>()
+            .release(button);
+        self
+    }
+
+    /// Picks an offer or skips the draft during the next step, like the draft screen does.
+    pub fn choose_draft(&mut self, choice: DraftChoice) -> &mut Self {
+        self.send(choice)
+    }
+
+    pub fn aim_at(&mut self, position: Vec2) -> &mut Self {
+        self.app.world.resource_mut::<MousePosition>().0 = position;
+        self
+    }
+
+    pub fn try_player(&mut self) -> Option<Entity> {
+        self.app
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .get_single(&self.app.world)
+            .ok()
+    }
+
+    pub fn player(&mut self) -> Entity {
+        self.try_player().expect("there is no player")
+    }
+
+    pub fn get<C: Component>(&self, entity: Entity) -> Option<&C> {
+        self.app.world.get::<C>(entity)
+    }
+
+    pub fn get_mut<C: Component>(&mut self, entity: Entity) -> Option<Mut<'_, C>> {
+        self.app.world.get_mut::<C>(entity)
+    }
+
+    pub fn exists(&self, entity: Entity) -> bool {
+        self.app.world.get_entity(entity).is_some()
+    }
+
+    /// Health of the entity, panics if it's gone.
+    pub fn health(&self, entity: Entity) -> f32 {
+        self.get::<Health>(entity).expect("entity has no health").0
+    }
+
+    pub fn player_health(&mut self) -> f32 {
+        let player = self.player();
+        self.health(player)
+    }
+
+    pub fn heat(&mut self) -> f32 {
+        let player = self.player();
+        self.get::<Heat>(player).expect("player has no heat").0
+    }
+
+    pub fn position(&self, entity: Entity) -> Vec2 {
+        self.get::<Transform>(entity)
+            .expect("entity has no transform")
+            .translation
+            .truncate()
+    }
+
+    /// Moves the entity, keeping its z.
+    pub fn set_position(&mut self, entity: Entity, position: Vec2) -> &mut Self {
+        let mut transform = self
+            .get_mut::<Transform>(entity)
+            .expect("entity has no transform");
+        transform.translation = position.extend(transform.translation.z);
+        self
+    }
+
+    pub fn state(&self) -> GameState {
+        self.app.world.resource::<State<GameState>>().0
+    }
+
+    /// Switches to the state and steps until the transition happened.
+    pub fn set_state(&mut self, state: GameState) -> &mut Self {
+        self.app
+            .world
+            .resource_mut::<NextState<GameState>>()
+            .set(state);
+        self.step()
+    }
+}
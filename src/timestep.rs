@@ -0,0 +1,264 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    ecs::schedule::{ExecutorKind, ScheduleLabel},
+    prelude::*,
+};
+
+use crate::state::GameState;
+
+/// Game time that passes every step, regardless of how long the frame actually took.
+pub const TIMESTEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Most steps taken in one update when catching up with real time, after that the game slows
+/// down instead of spending ever longer frames catching up.
+const MAX_CATCH_UP_STEPS: u32 = 4;
+
+/// Runs the [`GameStep`] schedule once per [`TIMESTEP`] of game time, so every gameplay system
+/// sees the same delta and the same seed and inputs reproduce the same run. Input, menus and
+/// everything else presentation keep running in `CoreSchedule::Main` every frame.
+pub struct TimestepPlugin;
+
+impl Plugin for TimestepPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StepRate>()
+            .init_step_schedule()
+            .add_system(run_fixed_steps.in_base_set(CoreSet::FixedUpdate));
+    }
+}
+
+/// How many steps are taken every time the app updates.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum StepRate {
+    /// As many as fit in the real time that passed since the last update
+    #[default]
+    RealTime,
+    /// As many as fit in this many times the real time that passed, to fast-forward replays
+    FastForward(u32),
+    /// Always the same number, to run as fast as possible headless or to fast-forward
+    PerUpdate(u32),
+}
+
+/// The gameplay and physics, run once per [`TIMESTEP`] from `CoreSet::FixedUpdate` and never
+/// while paused. Systems run one at a time, in the order the schedule sorts them into rather
+/// than the order they were added, which is the same every run so events and random numbers are
+/// too. Systems that depend on each other still have to be ordered explicitly. [`Time`] is the
+/// game time while it runs, and its events are kept for two steps instead of two frames.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GameStep;
+
+/// The base sets of [`GameStep`], like `CoreSet` for the main schedule. Systems without one run
+/// in [`StepSet::Update`], and physics runs between [`StepSet::UpdateFlush`] and
+/// [`StepSet::PostUpdate`].
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[system_set(base)]
+pub enum StepSet {
+    /// Events are updated and state transitions applied
+    First,
+    FirstFlush,
+    PreUpdate,
+    PreUpdateFlush,
+    Update,
+    UpdateFlush,
+    PostUpdate,
+    PostUpdateFlush,
+    Last,
+    LastFlush,
+}
+
+/// Adds gameplay to [`GameStep`] like the matching `App` methods add it to the main schedule.
+pub trait AddToStep {
+    /// Creates [`GameStep`] with its base sets, unless it exists already.
+    fn init_step_schedule(&mut self) -> &mut Self;
+
+    /// Adds an event that is updated every step, for events sent or read by gameplay.
+    fn add_step_event<T: Event>(&mut self) -> &mut Self;
+
+    /// Adds a step event that is also collected into [`FrameEvents`], for events read by the
+    /// presentation in the main schedule.
+    fn add_frame_step_event<T: Event + Clone>(&mut self) -> &mut Self;
+
+    fn add_step_system<M>(&mut self, system: impl IntoSystemConfig<M>) -> &mut Self;
+
+    fn add_step_systems<M>(&mut self, systems: impl IntoSystemConfigs<M>) -> &mut Self;
+
+    fn configure_step_set(&mut self, set: impl IntoSystemSetConfig) -> &mut Self;
+
+    fn configure_step_sets(&mut self, sets: impl IntoSystemSetConfigs) -> &mut Self;
+}
+
+impl AddToStep for App {
+    fn init_step_schedule(&mut self) -> &mut Self {
+        let mut schedules = self.world.resource_mut::<Schedules>();
+        if schedules.contains(&GameStep) {
+            return self;
+        }
+
+        let mut schedule = Schedule::new();
+        schedule
+            .set_executor_kind(ExecutorKind::SingleThreaded)
+            .set_default_base_set(StepSet::Update)
+            .configure_sets(
+                (
+                    StepSet::First,
+                    StepSet::FirstFlush,
+                    StepSet::PreUpdate,
+                    StepSet::PreUpdateFlush,
+                    StepSet::Update,
+                    StepSet::UpdateFlush,
+                    StepSet::PostUpdate,
+                    StepSet::PostUpdateFlush,
+                    StepSet::Last,
+                    StepSet::LastFlush,
+                )
+                    .chain(),
+            )
+            .add_systems((
+                apply_system_buffers.in_base_set(StepSet::FirstFlush),
+                apply_system_buffers.in_base_set(StepSet::PreUpdateFlush),
+                apply_system_buffers.in_base_set(StepSet::UpdateFlush),
+                apply_system_buffers.in_base_set(StepSet::PostUpdateFlush),
+                apply_system_buffers.in_base_set(StepSet::LastFlush),
+            ));
+        schedules.insert(GameStep, schedule);
+        self
+    }
+
+    fn add_step_event<T: Event>(&mut self) -> &mut Self {
+        if !self.world.contains_resource::<Events<T>>() {
+            self.init_resource::<Events<T>>()
+                .add_step_system(Events::<T>::update_system.in_base_set(StepSet::First));
+        }
+        self
+    }
+
+    fn add_frame_step_event<T: Event + Clone>(&mut self) -> &mut Self {
+        self.add_step_event::<T>();
+        if !self.world.contains_resource::<FrameEvents<T>>() {
+            self.init_resource::<FrameEvents<T>>()
+                .add_step_system(collect_frame_events::<T>.in_base_set(StepSet::Last))
+                .add_system(clear_frame_events::<T>.in_base_set(CoreSet::First));
+        }
+        self
+    }
+
+    fn add_step_system<M>(&mut self, system: impl IntoSystemConfig<M>) -> &mut Self {
+        self.init_step_schedule()
+            .add_system(system.in_schedule(GameStep))
+    }
+
+    fn add_step_systems<M>(&mut self, systems: impl IntoSystemConfigs<M>) -> &mut Self {
+        self.init_step_schedule()
+            .add_systems(systems.into_configs().in_schedule(GameStep))
+    }
+
+    fn configure_step_set(&mut self, set: impl IntoSystemSetConfig) -> &mut Self {
+        step_schedule(self).configure_set(set);
+        self
+    }
+
+    fn configure_step_sets(&mut self, sets: impl IntoSystemSetConfigs) -> &mut Self {
+        step_schedule(self).configure_sets(sets);
+        self
+    }
+}
+
+fn step_schedule(app: &mut App) -> &mut Schedule {
+    app.init_step_schedule()
+        .world
+        .resource_mut::<Schedules>()
+        .into_inner()
+        .get_mut(&GameStep)
+        .unwrap()
+}
+
+/// The events sent by the steps run this frame. Step events only last two steps, so an
+/// `EventReader` in the main schedule misses some whenever more than two steps run in a frame.
+#[derive(Resource)]
+pub struct FrameEvents<T: Event> {
+    events: Vec<T>,
+}
+
+impl<T: Event> Default for FrameEvents<T> {
+    fn default() -> Self {
+        Self { events: Vec::new() }
+    }
+}
+
+impl<T: Event> FrameEvents<T> {
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.events.iter()
+    }
+}
+
+fn collect_frame_events<T: Event + Clone>(
+    mut events: EventReader<T>,
+    mut frame_events: ResMut<FrameEvents<T>>,
+) {
+    frame_events.events.extend(events.iter().cloned());
+}
+
+fn clear_frame_events<T: Event>(mut frame_events: ResMut<FrameEvents<T>>) {
+    frame_events.events.clear();
+}
+
+/// Game time of the last step and the real time not yet stepped through.
+struct StepClock {
+    now: Instant,
+    last_update: Instant,
+    behind: Duration,
+    /// Swapped in for [`Time`] while the step runs
+    time: Time,
+}
+
+impl StepClock {
+    /// Steps that fit in `speed` times the real time since the last update.
+    fn real_time_steps(&mut self, speed: u32) -> u32 {
+        let now = Instant::now();
+        self.behind += (now - self.last_update) * speed;
+        self.last_update = now;
+
+        let max_steps = MAX_CATCH_UP_STEPS * speed;
+        let steps = (self.behind.as_nanos() / TIMESTEP.as_nanos()) as u32;
+        if steps > max_steps {
+            self.behind = Duration::ZERO;
+            max_steps
+        } else {
+            self.behind -= TIMESTEP * steps;
+            steps
+        }
+    }
+}
+
+fn run_fixed_steps(world: &mut World, mut clock: Local<Option<StepClock>>) {
+    let clock = clock.get_or_insert_with(|| {
+        let startup = world.resource::<Time>().startup();
+        // So the first step already has a delta
+        let mut time = Time::new(startup);
+        time.update_with_instant(startup);
+        StepClock {
+            now: startup,
+            last_update: Instant::now(),
+            behind: TIMESTEP,
+            time,
+        }
+    });
+
+    let steps = match *world.resource::<StepRate>() {
+        StepRate::RealTime => clock.real_time_steps(1),
+        StepRate::FastForward(speed) => clock.real_time_steps(speed),
+        StepRate::PerUpdate(steps) => steps,
+    };
+    // The time spent paused is dropped rather than caught up with
+    if world.resource::<State<GameState>>().0 == GameState::Paused {
+        return;
+    }
+
+    for _ in 0..steps {
+        clock.now += TIMESTEP;
+        clock.time.update_with_instant(clock.now);
+        let real_time = std::mem::replace(&mut *world.resource_mut::<Time>(), clock.time.clone());
+        world.run_schedule(GameStep);
+        *world.resource_mut::<Time>() = real_time;
+    }
+}
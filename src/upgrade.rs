@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use bevy_turborand::{DelegatedRng, RngComponent};
+
+use crate::{
+    abilities::{
+        activation::ActivationSet,
+        stats::{ModifierKind, PowerStats, Stat, StatModifier, StatModifiers},
+        AbilitySet, Loadout,
+    },
+    death::DeathEffectSet,
+    enemy::Enemy,
+    health::DeathEvent,
+    player::{CurrentAbility, Player},
+    seed::UpgradeRng,
+    state::{GameState, OnExitRun},
+    timestep::AddToStep,
+};
+
+pub struct UpgradePlugin;
+
+impl Plugin for UpgradePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<UpgradePickup>()
+            .add_step_systems(
+                (
+                    drop_upgrades.in_set(DeathEffectSet),
+                    collect_upgrades.after(ActivationSet).before(AbilitySet),
+                )
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(despawn_upgrades.in_schedule(OnExitRun));
+    }
+}
+
+const DROP_CHANCE: f64 = 0.15;
+const PICKUP_RADIUS: f32 = 12.0;
+
+const UPGRADES: [StatModifier; 5] = [
+    StatModifier {
+        stat: Stat::Cooldown,
+        kind: ModifierKind::Multiplicative,
+        value: 0.85,
+    },
+    StatModifier {
+        stat: Stat::Heat,
+        kind: ModifierKind::Multiplicative,
+        value: 0.8,
+    },
+    StatModifier {
+        stat: Stat::Damage,
+        kind: ModifierKind::Multiplicative,
+        value: 1.25,
+    },
+    StatModifier {
+        stat: Stat::Range,
+        kind: ModifierKind::Additive,
+        value: 16.0,
+    },
+    StatModifier {
+        stat: Stat::ProjectileCount,
+        kind: ModifierKind::Additive,
+        value: 1.0,
+    },
+];
+
+/// Adds its modifier to the player's selected ability when walked over, if the ability reads the
+/// upgraded stat.
+#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component, Default, Debug)]
+pub struct UpgradePickup(pub StatModifier);
+
+fn drop_upgrades(
+    mut commands: Commands,
+    mut death_events: EventReader<DeathEvent>,
+    enemies: Query<&Transform, With<Enemy>>,
+    player: Query<(&CurrentAbility, &Loadout), With<Player>>,
+    abilities: Query<&PowerStats>,
+    mut rngs: Query<&mut RngComponent, With<UpgradeRng>>,
+) {
+    let Ok(mut rng) = rngs.get_single_mut() else { return; };
+    // Only offer upgrades for stats the selected ability reads
+    let stats = player
+        .get_single()
+        .ok()
+        .and_then(|(current_ability, loadout)| loadout.abilities.get(current_ability.0))
+        .and_then(|&ability| abilities.get(ability).ok())
+        .copied()
+        .unwrap_or_default();
+    let upgrades: Vec<_> = UPGRADES
+        .iter()
+        .filter(|upgrade| stats.reads(upgrade.stat))
+        .collect();
+
+    for death in death_events.iter() {
+        let Ok(enemy_transform) = enemies.get(death.died_id) else { continue; };
+        if !rng.chance(DROP_CHANCE) {
+            continue;
+        }
+
+        let upgrade = **rng.sample(&upgrades).unwrap();
+        let color = match upgrade.stat {
+            Stat::Cooldown => Color::CYAN,
+            Stat::Heat => Color::ORANGE_RED,
+            Stat::Damage => Color::RED,
+            Stat::Range => Color::GREEN,
+            Stat::ProjectileCount => Color::YELLOW,
+        };
+
+        commands.spawn((
+            UpgradePickup(upgrade),
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(6.0)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(
+                    enemy_transform.translation.truncate().extend(0.5),
+                )
+                .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                ..Default::default()
+            },
+        ));
+    }
+}
+
+fn collect_upgrades(
+    mut commands: Commands,
+    player: Query<(&Transform, &CurrentAbility, &Loadout), With<Player>>,
+    pickups: Query<(Entity, &Transform, &UpgradePickup)>,
+    mut abilities: Query<(&mut StatModifiers, &PowerStats)>,
+) {
+    let Ok((player_transform, current_ability, loadout)) = player.get_single() else { return; };
+    let Some(&ability) = loadout.abilities.get(current_ability.0) else { return; };
+    let player_position = player_transform.translation.truncate();
+
+    for (pickup_id, pickup_transform, pickup) in &pickups {
+        let pickup_position = pickup_transform.translation.truncate();
+        if player_position.distance(pickup_position) > PICKUP_RADIUS {
+            continue;
+        }
+        let Ok((mut modifiers, stats)) = abilities.get_mut(ability) else { continue; };
+        // Left for an ability that can use it
+        if !stats.reads(pickup.0.stat) {
+            continue;
+        }
+
+        info!("Picked up {:?}", pickup.0);
+        modifiers.0.push(pickup.0);
+        commands.entity(pickup_id).despawn_recursive();
+    }
+}
+
+fn despawn_upgrades(mut commands: Commands, pickups: Query<Entity, With<UpgradePickup>>) {
+    for e in &pickups {
+        commands.entity(e).despawn_recursive();
+    }
+}
@@ -0,0 +1,480 @@
+use bevy::{asset::HandleId, ecs::event::ManualEventReader, prelude::*};
+use bevy_rapier2d::prelude::{CollisionEvent, CollisionEventFlags};
+use labrats::{
+    abilities::{
+        activation::{AbilityTriggerEvent, TriggerState},
+        cooldown::{AbilityCooldown, AbilityCooldownTime},
+        definition::AbilityDefinition,
+        fireball::{Delivery, Fireball, FireballPower},
+        heat::{Heat, Overheated},
+        stats::{ModifierKind, Stat, StatModifier, StatModifiers},
+        wall::IceWall,
+        AbilityRejected, Loadout, RejectionReason,
+    },
+    assets::GameAssets,
+    draft::{DraftChoice, DraftOffer, DraftOffers},
+    enemy::Enemy,
+    explosion::ExplosionEvent,
+    health::HitEvent,
+    projectile::Projectile,
+    state::GameState,
+    testing::TestHarness,
+    timestep::{FrameEvents, TIMESTEP},
+    upgrade::UpgradePickup,
+};
+
+const TELEPORT: usize = 0;
+const FIREBALL: usize = 1;
+const ICE_WALL: usize = 2;
+
+fn ice_walls(harness: &mut TestHarness) -> usize {
+    harness
+        .app
+        .world
+        .query_filtered::<(), With<IceWall>>()
+        .iter(&harness.app.world)
+        .count()
+}
+
+#[test]
+fn teleport_moves_towards_aim_up_to_its_range() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    // The teleport starts on cooldown so the click that starts the run doesn't use it
+    harness.step_seconds(1.1);
+
+    let player = harness.player();
+    let start = harness.position(player);
+    harness
+        .aim_at(start + Vec2::new(200.0, 0.0))
+        .use_ability(TELEPORT)
+        .step();
+
+    let moved = harness.position(player) - start;
+    assert!((moved.x - 64.0).abs() < 1.0, "moved by {moved}");
+    assert!(moved.y.abs() < 1.0, "moved by {moved}");
+}
+
+#[test]
+fn abilities_go_on_cooldown_after_use() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    harness.aim_at(Vec2::new(0.0, 48.0));
+
+    harness.use_ability(ICE_WALL).step();
+    assert_eq!(ice_walls(&mut harness), 1);
+
+    let player = harness.player();
+    let ice_wall = harness.get::<Loadout>(player).unwrap().abilities[ICE_WALL];
+    assert!(harness.get::<AbilityCooldown>(ice_wall).is_some());
+
+    harness.use_ability(ICE_WALL).step();
+    assert_eq!(ice_walls(&mut harness), 1, "used while on cooldown");
+
+    // The first wall melts before the three second cooldown is over
+    harness.step_seconds(3.0);
+    assert!(harness.get::<AbilityCooldown>(ice_wall).is_none());
+    harness.use_ability(ICE_WALL).step();
+    assert_eq!(ice_walls(&mut harness), 1);
+}
+
+#[test]
+fn abilities_add_heat_which_dissipates() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    harness.aim_at(Vec2::new(100.0, 0.0));
+
+    harness.use_ability(FIREBALL).step();
+    let heat = harness.heat();
+    assert!(heat > 35.0 && heat <= 40.0, "heat is {heat}");
+
+    harness.step_seconds(1.0);
+    assert!(harness.heat() < heat - 15.0, "heat is {}", harness.heat());
+
+    harness.step_seconds(2.0);
+    assert_eq!(harness.heat(), 0.0);
+}
+
+#[test]
+fn overheating_blocks_abilities_until_cooled_down() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    harness.aim_at(Vec2::new(0.0, 48.0));
+
+    let player = harness.player();
+    harness.get_mut::<Heat>(player).unwrap().0 = 150.0;
+    harness.step();
+    assert!(harness.get::<Overheated>(player).is_some());
+
+    harness.use_ability(ICE_WALL).step();
+    assert_eq!(ice_walls(&mut harness), 0, "used while overheated");
+
+    let cooled = harness.run_until(10.0, |harness| harness.get::<Overheated>(player).is_none());
+    assert!(cooled, "never cooled down");
+
+    harness.use_ability(ICE_WALL).step();
+    assert_eq!(ice_walls(&mut harness), 1);
+}
+
+#[test]
+fn definitions_loaded_after_the_ability_still_apply() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    let player = harness.player();
+    let fireball = harness.get::<Loadout>(player).unwrap().abilities[FIREBALL];
+    let definitions = harness.app.world.resource::<Assets<AbilityDefinition>>();
+    let definition = definitions
+        .get(harness.get::<Handle<AbilityDefinition>>(fireball).unwrap())
+        .unwrap()
+        .clone();
+
+    let handle = Handle::<AbilityDefinition>::weak(HandleId::random::<AbilityDefinition>());
+    let ability = harness.app.world.spawn(handle.clone()).id();
+    harness.step();
+    assert!(harness.get::<StatModifiers>(ability).is_none());
+
+    harness
+        .app
+        .world
+        .resource_mut::<Assets<AbilityDefinition>>()
+        .set_untracked(handle, definition);
+    // The asset event goes out at the end of the step
+    harness.step_frames(2);
+    assert!(harness.get::<StatModifiers>(ability).is_some());
+}
+
+#[test]
+fn triggering_abilities_that_havent_loaded_is_rejected() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    let player = harness.player();
+    let handle = Handle::<AbilityDefinition>::weak(HandleId::random::<AbilityDefinition>());
+    let ability = harness.app.world.spawn(handle).id();
+    let mut loadout = harness.get_mut::<Loadout>(player).unwrap();
+    loadout.abilities.push(ability);
+    let slot = loadout.abilities.len() - 1;
+
+    harness
+        .send(AbilityTriggerEvent {
+            loadout: player,
+            ability: slot,
+            state: TriggerState::Pressed,
+        })
+        .step();
+
+    let rejected = harness.app.world.resource::<FrameEvents<AbilityRejected>>();
+    let reasons: Vec<_> = rejected.iter().map(|rejection| rejection.reason).collect();
+    assert_eq!(reasons, [RejectionReason::NotLoaded]);
+}
+
+#[test]
+fn channeled_heat_only_follows_multiplicative_heat_upgrades() {
+    let modifiers = StatModifiers(vec![
+        StatModifier {
+            stat: Stat::Heat,
+            kind: ModifierKind::Additive,
+            value: 10.0,
+        },
+        StatModifier {
+            stat: Stat::Heat,
+            kind: ModifierKind::Multiplicative,
+            value: 0.5,
+        },
+        StatModifier {
+            stat: Stat::Damage,
+            kind: ModifierKind::Multiplicative,
+            value: 2.0,
+        },
+    ]);
+
+    assert_eq!(modifiers.multiplier(Stat::Heat), 0.5);
+    assert_eq!(modifiers.apply(Stat::Heat, 10.0), 10.0);
+}
+
+#[test]
+fn drafted_abilities_follow_both_definitions_when_reloaded() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    let assets = harness.app.world.resource::<GameAssets>().clone();
+    harness.set_state(GameState::Drafting);
+    harness.app.world.resource_mut::<DraftOffers>().0 = vec![DraftOffer {
+        power: assets.fireball_ability.clone(),
+        side_effects: assets.teleport_ability.clone(),
+    }];
+    harness
+        .choose_draft(DraftChoice::Take {
+            offer: 0,
+            slot: ICE_WALL,
+        })
+        .step_frames(2);
+
+    let player = harness.player();
+    let ability = harness.get::<Loadout>(player).unwrap().abilities[ICE_WALL];
+    let side_effects = |harness: &TestHarness| {
+        harness
+            .get::<Children>(ability)
+            .map_or(0, |children| children.len())
+    };
+    assert_eq!(side_effects(&harness), 1);
+
+    let mut definitions = harness
+        .app
+        .world
+        .resource_mut::<Assets<AbilityDefinition>>();
+    definitions
+        .get_mut(&assets.teleport_ability)
+        .unwrap()
+        .side_effects
+        .clear();
+    definitions
+        .get_mut(&assets.fireball_ability)
+        .unwrap()
+        .cooldown = 5.0;
+    // The asset events go out at the end of the step
+    harness.step_frames(2);
+
+    assert_eq!(side_effects(&harness), 0);
+    assert_eq!(harness.get::<AbilityCooldownTime>(ability).unwrap().0, 5.0);
+}
+
+#[test]
+fn drafted_abilities_keep_the_upgrades_of_their_slot() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    let assets = harness.app.world.resource::<GameAssets>().clone();
+    let player = harness.player();
+    let old_ability = harness.get::<Loadout>(player).unwrap().abilities[ICE_WALL];
+    let upgrade = StatModifier {
+        stat: Stat::Cooldown,
+        kind: ModifierKind::Multiplicative,
+        value: 0.5,
+    };
+    harness
+        .get_mut::<StatModifiers>(old_ability)
+        .unwrap()
+        .0
+        .push(upgrade);
+
+    harness.set_state(GameState::Drafting);
+    harness.app.world.resource_mut::<DraftOffers>().0 = vec![DraftOffer {
+        power: assets.fireball_ability.clone(),
+        side_effects: assets.teleport_ability.clone(),
+    }];
+    harness
+        .choose_draft(DraftChoice::Take {
+            offer: 0,
+            slot: ICE_WALL,
+        })
+        .step_frames(2);
+
+    let ability = harness.get::<Loadout>(player).unwrap().abilities[ICE_WALL];
+    assert_ne!(ability, old_ability);
+    let modifiers = harness.get::<StatModifiers>(ability).unwrap();
+    assert_eq!(modifiers.0.len(), 1);
+    assert_eq!(modifiers.0[0].stat, Stat::Cooldown);
+}
+
+#[test]
+fn projectile_fireballs_explode_at_the_cursor() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    let player = harness.player();
+    let fireball = harness.get::<Loadout>(player).unwrap().abilities[FIREBALL];
+    harness.get_mut::<FireballPower>(fireball).unwrap().delivery = Delivery::Projectile;
+
+    let target = harness.position(player) + Vec2::new(100.0, 0.0);
+    harness.aim_at(target).use_ability(FIREBALL).step();
+    let projectiles = harness
+        .app
+        .world
+        .query_filtered::<(), With<Projectile>>()
+        .iter(&harness.app.world)
+        .count();
+    assert_eq!(projectiles, 1);
+
+    let mut reader = ManualEventReader::<ExplosionEvent>::default();
+    let mut explosion = None;
+    harness.run_until(1.0, |harness| {
+        let events = harness.app.world.resource::<Events<ExplosionEvent>>();
+        explosion = reader.iter(events).next().map(|event| event.position);
+        explosion.is_some()
+    });
+    let explosion = explosion.expect("the fireball never exploded");
+    assert!(explosion.distance(target) < 8.0, "exploded at {explosion}");
+}
+
+#[test]
+fn projectile_fireballs_explode_once_when_they_hit_something_as_they_land() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[(Enemy::Cat, Vec2::new(150.0, 80.0))]);
+    let player = harness.player();
+    let fireball = harness.get::<Loadout>(player).unwrap().abilities[FIREBALL];
+    harness.get_mut::<FireballPower>(fireball).unwrap().delivery = Delivery::Projectile;
+
+    let target = harness.position(player) + Vec2::new(100.0, 0.0);
+    harness.aim_at(target).use_ability(FIREBALL).step();
+    let projectile = harness
+        .app
+        .world
+        .query_filtered::<Entity, With<Projectile>>()
+        .single(&harness.app.world);
+
+    // The flight ends during the next step, which also handles the hit
+    let mut fireball = harness.get_mut::<Fireball>(projectile).unwrap();
+    let almost_landed = fireball.timer.duration() - TIMESTEP / 2;
+    fireball.timer.set_elapsed(almost_landed);
+    harness.send(CollisionEvent::Started(
+        projectile,
+        enemies[0],
+        CollisionEventFlags::SENSOR,
+    ));
+
+    let mut reader = ManualEventReader::<ExplosionEvent>::default();
+    let mut explosions = 0;
+    for _ in 0..3 {
+        harness.step();
+        let events = harness.app.world.resource::<Events<ExplosionEvent>>();
+        explosions += reader.iter(events).count();
+    }
+    assert!(!harness.exists(projectile));
+    assert_eq!(explosions, 1);
+}
+
+#[test]
+fn explosion_only_projectiles_dont_hit_what_they_touch() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[(Enemy::Cat, Vec2::new(150.0, 80.0))]);
+    let cat = enemies[0];
+    let player = harness.player();
+    let fireball = harness.get::<Loadout>(player).unwrap().abilities[FIREBALL];
+    harness.get_mut::<FireballPower>(fireball).unwrap().delivery = Delivery::Projectile;
+
+    let target = harness.position(player) + Vec2::new(100.0, 0.0);
+    harness.aim_at(target).use_ability(FIREBALL).step();
+    let projectile = harness
+        .app
+        .world
+        .query_filtered::<Entity, With<Projectile>>()
+        .single(&harness.app.world);
+
+    // Explodes where it is, out of the cat's reach
+    harness.send(CollisionEvent::Started(
+        projectile,
+        cat,
+        CollisionEventFlags::SENSOR,
+    ));
+    let mut reader = ManualEventReader::<HitEvent>::default();
+    for _ in 0..3 {
+        harness.step();
+        let events = harness.app.world.resource::<Events<HitEvent>>();
+        assert!(reader.iter(events).all(|hit| hit.target != cat));
+    }
+    assert!(!harness.exists(projectile));
+    assert_eq!(harness.health(cat), 100.0);
+}
+
+#[test]
+fn range_upgrades_extend_the_fireball_cast_range_but_not_its_explosion() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    let player = harness.player();
+    let fireball = harness.get::<Loadout>(player).unwrap().abilities[FIREBALL];
+    harness
+        .get_mut::<FireballPower>(fireball)
+        .unwrap()
+        .max_cast_range = 40.0;
+    harness
+        .get_mut::<StatModifiers>(fireball)
+        .unwrap()
+        .0
+        .push(StatModifier {
+            stat: Stat::Range,
+            kind: ModifierKind::Additive,
+            value: 16.0,
+        });
+
+    let start = harness.position(player);
+    harness
+        .aim_at(start + Vec2::new(100.0, 0.0))
+        .use_ability(FIREBALL)
+        .step();
+    let (position, range) = harness
+        .app
+        .world
+        .query::<(&Transform, &Fireball)>()
+        .iter(&harness.app.world)
+        .map(|(transform, fireball)| (transform.translation.truncate(), fireball.range))
+        .next()
+        .expect("no fireball was cast");
+
+    let cast = position - start;
+    assert!((cast.x - 56.0).abs() < 1.0, "cast {cast} away");
+    assert_eq!(range, 32.0);
+}
+
+#[test]
+fn upgrades_are_left_for_abilities_that_read_their_stat() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    let player = harness.player();
+    let teleport = harness.get::<Loadout>(player).unwrap().abilities[TELEPORT];
+    let position = harness.position(player);
+    let drop_upgrade = |harness: &mut TestHarness, stat| {
+        let upgrade = UpgradePickup(StatModifier {
+            stat,
+            kind: ModifierKind::Additive,
+            value: 1.0,
+        });
+        let transform = Transform::from_translation(position.extend(0.5));
+        harness
+            .app
+            .world
+            .spawn((upgrade, TransformBundle::from_transform(transform)))
+            .id()
+    };
+
+    // The teleport is selected, which has no projectiles
+    let projectile_count = drop_upgrade(&mut harness, Stat::ProjectileCount);
+    let range = drop_upgrade(&mut harness, Stat::Range);
+    harness.step();
+
+    assert!(harness.exists(projectile_count));
+    assert!(!harness.exists(range));
+    let modifiers = harness.get::<StatModifiers>(teleport).unwrap();
+    assert_eq!(modifiers.0.len(), 1);
+    assert_eq!(modifiers.0[0].stat, Stat::Range);
+}
+
+#[test]
+fn ice_walls_block_casting_fireballs() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_enemies(&[]);
+    let player = harness.player();
+    let start = harness.position(player);
+
+    harness
+        .aim_at(start + Vec2::new(48.0, 0.0))
+        .use_ability(ICE_WALL)
+        .step();
+    harness
+        .aim_at(start + Vec2::new(100.0, 0.0))
+        .use_ability(FIREBALL)
+        .step();
+    let position = harness
+        .app
+        .world
+        .query_filtered::<&Transform, With<Fireball>>()
+        .single(&harness.app.world)
+        .translation
+        .truncate();
+
+    let cast = position - start;
+    assert!(cast.x < 48.0, "cast {cast} away, through the wall");
+}
+
+#[test]
+fn fireballs_need_a_positive_speed() {
+    assert!(ron::from_str::<FireballPower>("(speed: 128.0)").is_ok());
+    assert!(ron::from_str::<FireballPower>("(speed: 0.0)").is_err());
+    assert!(ron::from_str::<FireballPower>("(max_cast_range: -1.0)").is_err());
+}
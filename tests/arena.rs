@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use labrats::{
+    arena::{Arena, ArenaLayout, TILE_SIZE},
+    assets::GameAssets,
+    room::{Room, SpawnRoomEvent},
+    testing::TestHarness,
+};
+
+fn layouts(harness: &TestHarness) -> Vec<(Handle<ArenaLayout>, ArenaLayout)> {
+    let layouts = harness.app.world.resource::<Assets<ArenaLayout>>();
+    harness
+        .app
+        .world
+        .resource::<GameAssets>()
+        .arenas
+        .iter()
+        .map(|handle| (handle.clone(), layouts.get(handle).unwrap().clone()))
+        .collect()
+}
+
+#[test]
+fn wall_tiles_are_merged_into_rectangles() {
+    let layout = ArenaLayout {
+        tiles: ["#####", "#.#x#", "#...#", "#####"]
+            .map(String::from)
+            .to_vec(),
+    };
+
+    let walls = layout.walls();
+
+    // The top and bottom rows, the sides in between, and the pillar
+    assert_eq!(walls.len(), 5);
+    let pillar = Rect::new(-8.0, 0.0, 8.0, 16.0);
+    assert!(walls.contains(&pillar));
+    assert_eq!(layout.spawn_points(), vec![Vec2::new(16.0, 8.0)]);
+}
+
+#[test]
+fn layouts_leave_room_to_spawn() {
+    let harness = TestHarness::new();
+    let radius = TILE_SIZE / 2.0;
+
+    for (_, layout) in layouts(&harness) {
+        let walls = layout.walls();
+        let blocked = |position: Vec2| {
+            walls.iter().any(|wall| {
+                let closest = position.clamp(wall.min, wall.max);
+                closest.distance(position) < radius
+            })
+        };
+        assert!(
+            !blocked(Vec2::ZERO),
+            "{:?} has a wall on the player",
+            layout
+        );
+        for spawn in layout.spawn_points() {
+            assert!(!blocked(spawn), "{:?} has a wall on {spawn}", layout);
+        }
+    }
+}
+
+#[test]
+fn walls_stop_the_player() {
+    let mut harness = TestHarness::new();
+    harness.start_run();
+    let arena = harness
+        .app
+        .world
+        .query::<&Arena>()
+        .single(&harness.app.world);
+    let floor = arena.floor;
+
+    let player = harness.player();
+    harness
+        .set_position(player, Vec2::new(floor.max.x - 32.0, 0.0))
+        .press_key(KeyCode::D)
+        .step_seconds(1.0);
+
+    assert!(harness.position(player).x <= floor.max.x - TILE_SIZE / 2.0 + 0.5);
+}
+
+#[test]
+fn arenas_are_replaced_when_the_layout_changes() {
+    let mut harness = TestHarness::new();
+    harness.start_run();
+    let (layout, _) = layouts(&harness)[1].clone();
+
+    let player = harness.player();
+    harness.set_position(player, Vec2::new(100.0, 50.0));
+    harness
+        .send(SpawnRoomEvent {
+            room: Room {
+                enemies: Vec::new(),
+                layout: layout.clone(),
+            },
+        })
+        .step();
+
+    let arenas: Vec<_> = harness
+        .app
+        .world
+        .query::<&Arena>()
+        .iter(&harness.app.world)
+        .map(|arena| arena.layout.clone())
+        .collect();
+    assert_eq!(arenas, vec![layout]);
+    assert_eq!(harness.position(player), Vec2::ZERO);
+}
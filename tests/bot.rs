@@ -0,0 +1,34 @@
+use labrats::{bot::BotPlugin, room_manager::CurrentRoom, state::GameState, testing::TestHarness};
+
+fn harness_with_bot() -> TestHarness {
+    let mut harness = TestHarness::new();
+    harness.app.add_plugin(BotPlugin);
+    harness
+}
+
+#[test]
+fn bot_clears_rooms() {
+    let mut harness = harness_with_bot();
+    harness.start_run_with_seed(1);
+
+    let cleared = harness.run_until(60.0, |harness| {
+        harness.app.world.resource::<CurrentRoom>().0 >= 2
+    });
+    assert!(cleared, "the bot didn't clear two rooms");
+}
+
+#[test]
+fn bot_runs_can_be_replayed() {
+    let mut played = harness_with_bot();
+    played.start_run_with_seed(4);
+    played.step_seconds(10.0);
+    let player = played.player();
+    let position = played.position(player);
+
+    let mut replayed = TestHarness::new();
+    replayed.watch_replay(played.recording());
+
+    assert_eq!(replayed.state(), GameState::Playing);
+    let player = replayed.player();
+    assert_eq!(replayed.position(player), position);
+}
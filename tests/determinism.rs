@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use labrats::{
+    abilities::{
+        fireball::{Delivery, FireballPower},
+        Loadout,
+    },
+    enemy::Enemy,
+    health::{DamageDealtEvent, DamageEvent, DamageType},
+    player::CurrentAbility,
+    room_manager::CurrentRoom,
+    seed::RunSeed,
+    testing::TestHarness,
+    timestep::{FrameEvents, StepRate},
+};
+
+const FIREBALL: usize = 1;
+
+/// Plays a few seconds of the run the same way every time, shooting at the first enemy.
+fn play(harness: &mut TestHarness) -> (Vec2, f32, u32, Vec<Vec2>) {
+    for frame in 0..600 {
+        if harness.try_player().is_none() {
+            break;
+        }
+        if let Some(&enemy) = harness.enemies().first() {
+            let target = harness.position(enemy);
+            harness.aim_at(target);
+        }
+        if frame % 20 == 0 {
+            harness.use_ability(frame / 20 % 3);
+        }
+        harness.step();
+    }
+
+    let player = harness.try_player();
+    let enemies = harness.enemies();
+    (
+        player.map_or(Vec2::ZERO, |player| harness.position(player)),
+        player.map_or(0.0, |player| harness.health(player)),
+        harness.app.world.resource::<CurrentRoom>().0,
+        enemies
+            .into_iter()
+            .map(|enemy| harness.position(enemy))
+            .collect(),
+    )
+}
+
+#[test]
+fn the_same_seed_and_inputs_play_the_same_run() {
+    let mut first = TestHarness::with_seed(1);
+    first.start_run_with_seed(7);
+    let mut second = TestHarness::with_seed(2);
+    second.start_run_with_seed(7);
+
+    assert_eq!(play(&mut first), play(&mut second));
+}
+
+#[test]
+fn frames_without_a_step_leave_gameplay_alone_but_keep_the_inputs() {
+    let mut harness = TestHarness::new();
+    harness.start_run();
+    let player = harness.player();
+    let start = harness.position(player);
+
+    harness.app.insert_resource(StepRate::PerUpdate(0));
+    harness
+        .press_key(KeyCode::D)
+        .press_key(KeyCode::Key2)
+        .step()
+        .release_key(KeyCode::Key2)
+        .step_frames(3);
+    assert_eq!(harness.position(player), start);
+    assert_eq!(harness.get::<CurrentAbility>(player).unwrap().0, 0);
+
+    harness.app.insert_resource(StepRate::PerUpdate(1));
+    harness.step_frames(5);
+    assert!(harness.position(player).x > start.x);
+    assert_eq!(harness.get::<CurrentAbility>(player).unwrap().0, 1);
+}
+
+#[test]
+fn events_from_every_step_of_a_frame_reach_the_presentation() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[(Enemy::Cat, Vec2::new(150.0, 80.0))]);
+
+    // Dealt on the first step, so its event is gone from the step events by the last one
+    harness.app.insert_resource(StepRate::PerUpdate(4));
+    harness
+        .send(DamageEvent {
+            damaged_id: enemies[0],
+            damage: 10.0,
+            source: None,
+            damage_type: DamageType::Melee,
+            hit: true,
+        })
+        .step();
+
+    let dealt = harness
+        .app
+        .world
+        .resource::<FrameEvents<DamageDealtEvent>>()
+        .iter()
+        .filter(|dealt| dealt.target == enemies[0])
+        .count();
+    assert_eq!(dealt, 1);
+}
+
+#[test]
+fn projectiles_hit_with_several_frames_per_step() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[(Enemy::Cat, Vec2::new(64.0, 0.0))]);
+    let cat = enemies[0];
+    let player = harness.player();
+    let fireball = harness.get::<Loadout>(player).unwrap().abilities[FIREBALL];
+    harness.get_mut::<FireballPower>(fireball).unwrap().delivery = Delivery::Projectile;
+
+    // Aimed past the cat, so only hitting it on the way explodes near it
+    let start = harness.position(player);
+    harness.aim_at(start + Vec2::new(160.0, 0.0)).use_ability(FIREBALL);
+    let mut hit = false;
+    for _ in 0..60 {
+        harness.app.insert_resource(StepRate::PerUpdate(1));
+        harness.step();
+        harness.app.insert_resource(StepRate::PerUpdate(0));
+        harness.step_frames(2);
+        if harness.health(cat) < 100.0 {
+            hit = true;
+            break;
+        }
+    }
+    assert!(hit, "the fireball never hit the cat");
+}
+
+#[test]
+fn the_run_seed_is_kept_for_the_death_screen() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_seed(0xC0FFEE);
+
+    assert_eq!(*harness.app.world.resource::<RunSeed>(), RunSeed(0xC0FFEE));
+}
+
+#[test]
+fn seeds_are_shown_and_entered_as_hex() {
+    let seed = RunSeed(0xC0FFEE);
+    assert_eq!(seed.to_string(), "0000000000C0FFEE");
+    assert_eq!(RunSeed::parse("0000000000C0FFEE"), Some(seed));
+    assert_eq!(RunSeed::parse("c0ffee"), Some(seed));
+    assert_eq!(RunSeed::parse(""), None);
+    assert_eq!(RunSeed::parse("10000000000000000"), None);
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use labrats::{
+    enemy::{
+        bomb::Bomb,
+        cat::{Cat, CatState},
+        Enemy,
+    },
+    health::Shield,
+    testing::TestHarness,
+};
+
+/// The shield absorbs the first hits, so check it as well as the health.
+fn player_was_hurt(harness: &mut TestHarness) -> bool {
+    let player = harness.player();
+    let shield = harness.get::<Shield>(player).unwrap();
+    shield.amount < shield.max || harness.player_health() < 100.0
+}
+
+#[test]
+fn spawn_events_spawn_the_right_enemy() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[
+        (Enemy::Bomb, Vec2::new(-150.0, 80.0)),
+        (Enemy::Cat, Vec2::new(150.0, 80.0)),
+    ]);
+    let [bomb, cat] = enemies[..] else {
+        unreachable!()
+    };
+
+    assert!(harness.get::<Bomb>(bomb).is_some());
+    assert!(harness.get::<Cat>(bomb).is_none());
+    assert!(harness.get::<Cat>(cat).is_some());
+    assert_eq!(harness.get::<CatState>(cat), Some(&CatState::Tracking));
+    assert_eq!(harness.position(cat), Vec2::new(150.0, 80.0));
+}
+
+#[test]
+fn bombs_chase_the_player_and_explode() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[(Enemy::Bomb, Vec2::new(-120.0, 0.0))]);
+    let bomb = enemies[0];
+
+    harness.step_seconds(1.0);
+    assert!(harness.position(bomb).x > -120.0, "the bomb didn't move");
+
+    let exploded = harness.run_until(20.0, |harness| !harness.exists(bomb));
+    assert!(exploded, "the bomb never exploded");
+    assert!(player_was_hurt(&mut harness));
+}
+
+#[test]
+fn cats_circle_then_pounce_on_the_player() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[(Enemy::Cat, Vec2::new(100.0, 0.0))]);
+    let cat = enemies[0];
+
+    // Tracks for two seconds once in range before jumping
+    harness.step_seconds(1.0);
+    assert_eq!(harness.get::<CatState>(cat), Some(&CatState::Tracking));
+
+    let pounced = harness.run_until(5.0, |harness| {
+        harness.get::<CatState>(cat) == Some(&CatState::Attacking)
+    });
+    assert!(pounced, "the cat never pounced");
+
+    let bitten = harness.run_until(10.0, player_was_hurt);
+    assert!(bitten, "the cat never bit the player");
+}
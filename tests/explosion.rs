@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Velocity;
+use labrats::{enemy::Enemy, explosion::ExplosionEvent, testing::TestHarness};
+
+fn explosion(position: Vec2) -> ExplosionEvent {
+    ExplosionEvent {
+        position,
+        range: 64.0,
+        force: 100.0,
+        damage: 40.0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn explosions_deal_less_damage_further_away() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[
+        (Enemy::Cat, Vec2::new(-150.0, 50.0)),
+        (Enemy::Cat, Vec2::new(-150.0, 90.0)),
+        (Enemy::Cat, Vec2::new(-150.0, -50.0)),
+    ]);
+    let [near, far, out_of_range] = enemies[..] else { unreachable!() };
+
+    harness.send(explosion(Vec2::new(-150.0, 40.0))).step();
+
+    let near_damage = 100.0 - harness.health(near);
+    let far_damage = 100.0 - harness.health(far);
+    assert!(near_damage > far_damage, "{near_damage} <= {far_damage}");
+    assert!(far_damage > 0.0);
+    assert_eq!(harness.health(out_of_range), 100.0);
+}
+
+#[test]
+fn explosions_push_things_away() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[(Enemy::Cat, Vec2::new(-150.0, 50.0))]);
+
+    harness
+        .send(explosion(Vec2::new(-182.0, 50.0)))
+        .step_frames(2);
+
+    let velocity = harness.get::<Velocity>(enemies[0]).unwrap().linvel;
+    assert!(velocity.x > 0.0, "velocity is {velocity}");
+}
+
+#[test]
+fn bombs_explode_when_they_die() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[
+        (Enemy::Bomb, Vec2::new(-150.0, 50.0)),
+        (Enemy::Cat, Vec2::new(-150.0, 80.0)),
+    ]);
+    let [bomb, cat] = enemies[..] else { unreachable!() };
+
+    // Big enough to kill the bomb, but out of the cat's range
+    harness
+        .send(ExplosionEvent {
+            position: Vec2::new(-150.0, 40.0),
+            range: 16.0,
+            damage: 1000.0,
+            ..Default::default()
+        })
+        .step_frames(2);
+
+    assert!(!harness.exists(bomb));
+    assert!(harness.health(cat) < 100.0);
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use labrats::{
+    abilities::{definition::AbilityDefinition, Loadout},
+    draft::DraftChoice,
+    replay::Replay,
+    seed::RunSeed,
+    state::GameState,
+    testing::TestHarness,
+};
+
+/// Where everything is once a run was played or replayed.
+fn snapshot(harness: &mut TestHarness) -> (GameState, Option<(Vec2, f32)>, Vec<Vec2>) {
+    let player = harness
+        .try_player()
+        .map(|player| (harness.position(player), harness.health(player)));
+    let enemies = harness.enemies();
+    (
+        harness.state(),
+        player,
+        enemies
+            .into_iter()
+            .map(|enemy| harness.position(enemy))
+            .collect(),
+    )
+}
+
+/// Moves around and uses every ability at the first enemy with the default bindings.
+fn play(harness: &mut TestHarness) {
+    let abilities = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+    for frame in 0..400 {
+        if harness.try_player().is_none() {
+            break;
+        }
+        if let Some(&enemy) = harness.enemies().first() {
+            let target = harness.position(enemy);
+            harness.aim_at(target);
+        }
+        match frame % 100 {
+            0 => harness.press_key(KeyCode::D),
+            30 => harness.release_key(KeyCode::D).press_key(KeyCode::W),
+            60 => harness.release_key(KeyCode::W),
+            _ => harness,
+        };
+        match frame % 25 {
+            0 => harness.press_key(abilities[frame / 25 % abilities.len()]),
+            1 => harness.press_mouse(MouseButton::Left),
+            5 => {
+                for key in abilities {
+                    harness.release_key(key);
+                }
+                harness.release_mouse(MouseButton::Left)
+            }
+            _ => harness,
+        };
+        harness.step();
+    }
+}
+
+#[test]
+fn replays_play_the_recorded_run_again() {
+    let mut recorded = TestHarness::with_seed(1);
+    recorded.start_run_with_seed(3);
+    play(&mut recorded);
+    let replay = recorded.recording();
+
+    let mut replayed = TestHarness::with_seed(2);
+    replayed.watch_replay(replay.clone());
+
+    assert_eq!(replayed.recording(), replay);
+    assert_eq!(snapshot(&mut replayed), snapshot(&mut recorded));
+}
+
+#[test]
+fn draft_picks_are_replayed() {
+    let mut recorded = TestHarness::new();
+    recorded.start_run_with_seed(5);
+    // The bombs of the first room blow themselves up on the player
+    assert!(recorded.run_until(20.0, |harness| harness.state() == GameState::Drafting));
+    recorded
+        .choose_draft(DraftChoice::Take { offer: 1, slot: 2 })
+        .step_frames(2);
+    assert_eq!(recorded.state(), GameState::Playing);
+
+    let mut replayed = TestHarness::new();
+    replayed.watch_replay(recorded.recording());
+
+    let ability_name = |harness: &mut TestHarness| {
+        let player = harness.player();
+        let ability = harness.get::<Loadout>(player).unwrap().abilities[2];
+        let handle = harness.get::<Handle<AbilityDefinition>>(ability).unwrap();
+        let definitions = harness.app.world.resource::<Assets<AbilityDefinition>>();
+        definitions.get(handle).unwrap().name.clone()
+    };
+    assert_eq!(replayed.state(), GameState::Playing);
+    assert_eq!(ability_name(&mut replayed), ability_name(&mut recorded));
+    assert_eq!(snapshot(&mut replayed), snapshot(&mut recorded));
+}
+
+#[test]
+fn replays_are_saved_and_loaded() {
+    let mut harness = TestHarness::new();
+    harness.start_run_with_seed(0xBEEF);
+    harness.press_key(KeyCode::A).step_frames(30);
+    let replay = harness.recording();
+    assert_eq!(replay.seed, RunSeed(0xBEEF));
+
+    let path = std::env::temp_dir().join(format!("labrats-replay-{}.ron", std::process::id()));
+    replay.save(&path);
+    let loaded = Replay::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, Some(replay));
+}
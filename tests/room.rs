@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use labrats::{
+    enemy::Enemy,
+    health::{DamageEvent, DamageType, Health},
+    room_manager::CurrentRoom,
+    state::GameState,
+    testing::TestHarness,
+};
+
+fn enemy_positions(harness: &mut TestHarness) -> Vec<Vec2> {
+    let enemies = harness.enemies();
+    enemies
+        .into_iter()
+        .map(|enemy| harness.position(enemy))
+        .collect()
+}
+
+fn kill(harness: &mut TestHarness, entity: Entity) {
+    harness.send(DamageEvent {
+        damaged_id: entity,
+        damage: 1000.0,
+        source: None,
+        damage_type: DamageType::Melee,
+        hit: true,
+    });
+}
+
+#[test]
+fn first_room_has_two_bombs() {
+    let mut harness = TestHarness::new();
+    harness.start_run();
+
+    let enemies = harness.enemies();
+    assert_eq!(enemies.len(), 2);
+    for enemy in enemies {
+        assert_eq!(harness.get::<Enemy>(enemy), Some(&Enemy::Bomb));
+    }
+}
+
+#[test]
+fn clearing_a_room_heals_and_starts_drafting() {
+    let mut harness = TestHarness::new();
+    let enemies = harness.start_run_with_enemies(&[(Enemy::Cat, Vec2::new(150.0, 80.0))]);
+
+    let player = harness.player();
+    harness.get_mut::<Health>(player).unwrap().0 = 40.0;
+    kill(&mut harness, enemies[0]);
+
+    assert!(harness.run_until(1.0, |harness| harness.state() == GameState::Drafting));
+    assert_eq!(harness.app.world.resource::<CurrentRoom>().0, 1);
+    assert_eq!(harness.player_health(), 100.0);
+}
+
+#[test]
+fn leaving_drafting_spawns_the_next_room() {
+    let mut harness = TestHarness::new();
+    harness.start_run();
+    for enemy in harness.enemies() {
+        kill(&mut harness, enemy);
+    }
+    assert!(harness.run_until(1.0, |harness| harness.state() == GameState::Drafting));
+
+    harness.set_state(GameState::Playing).step();
+
+    assert_eq!(harness.state(), GameState::Playing);
+    assert!(harness.enemies().len() >= 2);
+}
+
+#[test]
+fn player_dying_ends_the_run() {
+    let mut harness = TestHarness::new();
+    harness.start_run();
+
+    let player = harness.player();
+    kill(&mut harness, player);
+
+    assert!(harness.run_until(1.0, |harness| harness.state() == GameState::DeathScreen));
+    assert!(harness.try_player().is_none());
+}
+
+#[test]
+fn rooms_are_the_same_for_the_same_seed() {
+    let mut first = TestHarness::with_seed(42);
+    first.start_run();
+    let mut second = TestHarness::with_seed(42);
+    second.start_run();
+
+    assert_eq!(enemy_positions(&mut first), enemy_positions(&mut second));
+}